@@ -1,11 +1,5 @@
-use std::{env, fs};
-use std::path::PathBuf;
-use fs_extra::dir::CopyOptions;
-
 fn main() {
-    let out_dir = env::var("OUT_DIR").unwrap();
-    let dest_path = PathBuf::from(out_dir).join("stubs");
-    fs::create_dir_all(&dest_path).unwrap();
-    fs_extra::dir::copy("src/stubs", &dest_path, &CopyOptions::new().overwrite(true)).unwrap();
+    // Stubs are embedded with include_dir!, rebuild when they change
+    println!("cargo:rerun-if-changed=src/stubs");
     tauri_build::build()
 }
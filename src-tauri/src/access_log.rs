@@ -0,0 +1,115 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+// One request as written by the `valet_json` log format in nginx.conf
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct AccessLogEntry {
+    pub time: String,
+    #[serde(default)]
+    pub remote_addr: String,
+    pub method: String,
+    pub uri: String,
+    pub status: u16,
+    #[serde(default)]
+    pub bytes: u64,
+    // Seconds, as nginx reports them
+    pub request_time: f64,
+    #[serde(default, deserialize_with = "upstream_time")]
+    pub upstream_time: Option<f64>,
+    #[serde(default)]
+    pub host: String,
+    #[serde(default)]
+    pub user_agent: String,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct EndpointStats {
+    pub method: String,
+    pub path: String,
+    pub count: u64,
+    pub avg_ms: f64,
+    pub max_ms: f64,
+    pub errors: u64,
+}
+
+#[derive(Serialize, Clone, Debug, Default)]
+pub struct AccessLogStats {
+    pub total: u64,
+    pub status_2xx: u64,
+    pub status_3xx: u64,
+    pub status_4xx: u64,
+    pub status_5xx: u64,
+    pub avg_ms: f64,
+    pub slowest: Vec<EndpointStats>,
+}
+
+// `$upstream_response_time` is "-" without upstream and a list when nginx retried
+fn upstream_time<'de, D>(deserializer: D) -> Result<Option<f64>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let value = String::deserialize(deserializer)?;
+    Ok(value.split(',')
+        .filter_map(|time| time.trim().parse::<f64>().ok())
+        .reduce(|total, time| total + time))
+}
+
+impl AccessLogEntry {
+    // Newest last; lines that are not valet_json entries are skipped
+    pub fn parse_log(content: &str) -> Vec<AccessLogEntry> {
+        content.lines()
+            .filter_map(|line| serde_json::from_str(line.trim()).ok())
+            .collect()
+    }
+
+    // Path without the query string, so /users?page=2 and /users?page=3 group together
+    pub fn path(&self) -> &str {
+        self.uri.split('?').next().unwrap_or_default()
+    }
+}
+
+impl AccessLogStats {
+    pub fn from_entries(entries: &[AccessLogEntry], slowest: usize) -> Self {
+        let mut stats = AccessLogStats { total: entries.len() as u64, ..Default::default() };
+        let mut endpoints: HashMap<(String, String), EndpointStats> = HashMap::new();
+        let mut total_ms = 0.0;
+
+        for entry in entries {
+            match entry.status {
+                200..=299 => stats.status_2xx += 1,
+                300..=399 => stats.status_3xx += 1,
+                400..=499 => stats.status_4xx += 1,
+                500..=599 => stats.status_5xx += 1,
+                _ => {}
+            }
+            let ms = entry.request_time * 1000.0;
+            total_ms += ms;
+
+            let endpoint = endpoints.entry((entry.method.clone(), entry.path().to_string()))
+                .or_insert_with(|| EndpointStats {
+                    method: entry.method.clone(),
+                    path: entry.path().to_string(),
+                    count: 0,
+                    avg_ms: 0.0,
+                    max_ms: 0.0,
+                    errors: 0,
+                });
+            endpoint.avg_ms = (endpoint.avg_ms * endpoint.count as f64 + ms) / (endpoint.count + 1) as f64;
+            endpoint.count += 1;
+            endpoint.max_ms = endpoint.max_ms.max(ms);
+            if entry.status >= 500 {
+                endpoint.errors += 1;
+            }
+        }
+
+        if stats.total > 0 {
+            stats.avg_ms = total_ms / stats.total as f64;
+        }
+        let mut endpoints: Vec<EndpointStats> = endpoints.into_values().collect();
+        endpoints.sort_by(|a, b| b.avg_ms.total_cmp(&a.avg_ms));
+        endpoints.truncate(slowest);
+        stats.slowest = endpoints;
+        stats
+    }
+}
@@ -0,0 +1,96 @@
+use crate::commands::{memcached, redis};
+use crate::services::CacheStats;
+use crate::services::memcached::MemcachedSettings;
+use crate::services::redis::RedisSettings;
+
+#[tauri::command]
+pub fn redis_install() {
+    redis().install()
+}
+
+#[tauri::command]
+pub fn redis_start() {
+    redis().start()
+}
+
+#[tauri::command]
+pub fn redis_stop() {
+    redis().stop()
+}
+
+#[tauri::command]
+pub fn redis_restart() {
+    redis().restart()
+}
+
+#[tauri::command]
+pub fn redis_uninstall() {
+    redis().uninstall()
+}
+
+#[tauri::command]
+pub fn redis_settings() -> RedisSettings {
+    redis().settings()
+}
+
+#[tauri::command]
+pub fn set_redis_settings(settings: RedisSettings) -> Result<(), String> {
+    redis().set_settings(settings)
+}
+
+#[tauri::command]
+pub fn redis_flush() -> Result<(), String> {
+    redis().flush()
+}
+
+// Key count and memory usage for the dashboard
+#[tauri::command]
+pub fn redis_stats() -> Result<CacheStats, String> {
+    redis().stats()
+}
+
+#[tauri::command]
+pub fn memcached_install() {
+    memcached().install()
+}
+
+#[tauri::command]
+pub fn memcached_start() {
+    memcached().start()
+}
+
+#[tauri::command]
+pub fn memcached_stop() {
+    memcached().stop()
+}
+
+#[tauri::command]
+pub fn memcached_restart() {
+    memcached().restart()
+}
+
+#[tauri::command]
+pub fn memcached_uninstall() {
+    memcached().uninstall()
+}
+
+#[tauri::command]
+pub fn memcached_settings() -> MemcachedSettings {
+    memcached().settings()
+}
+
+#[tauri::command]
+pub fn set_memcached_settings(settings: MemcachedSettings) -> Result<(), String> {
+    memcached().set_settings(settings)
+}
+
+#[tauri::command]
+pub fn memcached_flush() -> Result<(), String> {
+    memcached().flush()
+}
+
+// Key count and memory usage for the dashboard
+#[tauri::command]
+pub fn memcached_stats() -> Result<CacheStats, String> {
+    memcached().stats()
+}
@@ -0,0 +1,90 @@
+use crate::commands::{mysql, postgres};
+
+// Install MySQL or MariaDB ("mysql" / "mariadb")
+#[tauri::command]
+pub fn database_install(flavor: String) -> Result<(), String> {
+    mysql().install(&flavor)
+}
+
+#[tauri::command]
+pub fn database_start() {
+    mysql().start()
+}
+
+#[tauri::command]
+pub fn database_stop() {
+    mysql().stop()
+}
+
+#[tauri::command]
+pub fn database_restart() {
+    mysql().restart()
+}
+
+#[tauri::command]
+pub fn databases() -> Result<Vec<String>, String> {
+    mysql().databases()
+}
+
+#[tauri::command]
+pub fn database_create(name: String) -> Result<(), String> {
+    mysql().create(&name)
+}
+
+#[tauri::command]
+pub fn database_drop(name: String) -> Result<(), String> {
+    mysql().drop(&name)
+}
+
+// Import a .sql or .sql.gz dump into a database
+#[tauri::command]
+pub fn database_import(name: String, path: String) -> Result<(), String> {
+    mysql().import(&name, &path)
+}
+
+// Export a database to a .sql or .sql.gz dump
+#[tauri::command]
+pub fn database_export(name: String, path: String) -> Result<(), String> {
+    mysql().export(&name, &path)
+}
+
+#[tauri::command]
+pub fn postgres_install() -> Result<(), String> {
+    postgres().install()
+}
+
+#[tauri::command]
+pub fn postgres_start() {
+    postgres().start()
+}
+
+#[tauri::command]
+pub fn postgres_stop() {
+    postgres().stop()
+}
+
+#[tauri::command]
+pub fn postgres_restart() {
+    postgres().restart()
+}
+
+#[tauri::command]
+pub fn postgres_databases() -> Result<Vec<String>, String> {
+    postgres().databases()
+}
+
+#[tauri::command]
+pub fn postgres_create(name: String) -> Result<(), String> {
+    postgres().create(&name)
+}
+
+#[tauri::command]
+pub fn postgres_drop(name: String) -> Result<(), String> {
+    postgres().drop(&name)
+}
+
+// Toggle creating a Postgres database named after each newly linked site
+#[tauri::command]
+pub fn set_postgres_site_databases(enabled: bool) {
+    postgres().set_creates_site_databases(enabled)
+}
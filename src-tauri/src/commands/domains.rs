@@ -0,0 +1,41 @@
+use tauri::{AppHandle, Manager};
+
+use crate::commands::{dnsmasq, mailpit, nginx, site};
+use crate::configuration::Configuration;
+use crate::domain::{DomainChange, DomainCheck};
+use crate::manager::command::ValetCommandLine;
+use crate::manager::file_system::ValetFilesystem;
+use crate::site_secure::SiteSecure;
+
+// Every TLD sites are served on, the primary one first
+#[tauri::command]
+pub fn domains() -> Vec<String> {
+    site().domains()
+}
+
+// Serve sites on several TLDs at once, e.g. ["test", "localhost"]
+#[tauri::command]
+pub fn set_domains(domains: Vec<String>) -> Result<Vec<String>, String> {
+    let domains = site().set_domains(domains)?;
+    dnsmasq().update_domains(&domains);
+    nginx().restart();
+    Ok(domains)
+}
+
+// Whether the TLD is usable, with a warning for HSTS preloaded and real TLDs
+#[tauri::command]
+pub fn check_domain(domain: String) -> Result<DomainCheck, String> {
+    DomainChange::check(&domain)
+}
+
+// Change the primary TLD, emitting "domain-progress" events; `force` accepts a TLD check warning
+#[tauri::command]
+pub fn set_domain(app: AppHandle, domain: String, force: bool) -> Result<(), String> {
+    let files = ValetFilesystem;
+    let config = Configuration::new(files);
+    let site_secure = SiteSecure::new(files, ValetCommandLine, config);
+    DomainChange::new(files, config, site_secure, dnsmasq(), mailpit(), nginx())
+        .set_domain(&domain, force, |progress| {
+            let _ = app.emit_all("domain-progress", progress);
+        })
+}
@@ -0,0 +1,34 @@
+use tauri::{AppHandle, Manager, State};
+
+use crate::commands::{logs, registry};
+use crate::logs::{LogEntry, LogFilter, LogSource, LogWatcher};
+
+#[tauri::command]
+pub fn log_sources() -> Vec<LogSource> {
+    logs().sources(&registry())
+}
+
+#[tauri::command]
+pub fn log_tail(source: String, lines: usize, filter: Option<LogFilter>) -> Result<Vec<LogEntry>, String> {
+    let logs = logs();
+    let source = logs.source(&registry(), &source)?;
+    logs.tail(&source, lines, &filter.unwrap_or_default())
+}
+
+// Stream new lines of the given logs as "log-entry" events until unwatched
+#[tauri::command]
+pub fn log_watch(app: AppHandle, watcher: State<LogWatcher>, sources: Vec<String>, filter: Option<LogFilter>) -> Result<(), String> {
+    let logs = logs();
+    let services = registry();
+    let sources = sources.iter()
+        .map(|id| logs.source(&services, id))
+        .collect::<Result<Vec<LogSource>, String>>()?;
+    watcher.watch(sources, filter.unwrap_or_default(), logs.domains(), move |entry| {
+        let _ = app.emit_all("log-entry", entry);
+    })
+}
+
+#[tauri::command]
+pub fn log_unwatch(watcher: State<LogWatcher>) {
+    watcher.unwatch()
+}
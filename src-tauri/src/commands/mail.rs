@@ -0,0 +1,53 @@
+use tauri::{AppHandle, Manager, State};
+
+use crate::configuration::Configuration;
+use crate::mail_catcher::{MailCatcher, MailCatcherSettings, MailMessage, MailSummary};
+use crate::manager::file_system::ValetFilesystem;
+
+// Forward every caught message to the UI so the inbox refreshes live
+pub(crate) fn notify(app: AppHandle) -> impl Fn(MailSummary) + Send + Sync + 'static {
+    move |mail| {
+        let _ = app.emit_all("mail-received", mail);
+    }
+}
+
+#[tauri::command]
+pub fn mail_catcher_settings() -> MailCatcherSettings {
+    MailCatcher::settings(&Configuration::new(ValetFilesystem))
+}
+
+#[tauri::command]
+pub fn set_mail_catcher_settings(app: AppHandle, catcher: State<MailCatcher>, settings: MailCatcherSettings) -> Result<(), String> {
+    catcher.set_settings(&Configuration::new(ValetFilesystem), settings, notify(app))
+}
+
+#[tauri::command]
+pub fn mail_catcher_running(catcher: State<MailCatcher>) -> bool {
+    catcher.is_running()
+}
+
+// Newest first, optionally filtered on subject, addresses and text body
+#[tauri::command]
+pub fn mails(query: Option<String>) -> Vec<MailSummary> {
+    MailCatcher::list(query.as_deref())
+}
+
+#[tauri::command]
+pub fn mail(id: String) -> Result<MailMessage, String> {
+    MailCatcher::read(&id)
+}
+
+#[tauri::command]
+pub fn mail_delete(id: String) -> Result<(), String> {
+    MailCatcher::delete(&id)
+}
+
+#[tauri::command]
+pub fn mail_clear() -> Result<(), String> {
+    MailCatcher::clear()
+}
+
+#[tauri::command]
+pub fn mail_save_attachment(id: String, index: usize, path: String) -> Result<(), String> {
+    MailCatcher::save_attachment(&id, index, &path)
+}
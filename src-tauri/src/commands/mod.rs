@@ -0,0 +1,122 @@
+use crate::configuration::Configuration;
+use crate::dnsmasq::DnsMasq;
+use crate::logs::Logs;
+use crate::mailpit::Mailpit;
+use crate::manager::apt::Apt;
+use crate::manager::command::ValetCommandLine;
+use crate::manager::file_system::ValetFilesystem;
+use crate::manager::service_manager::ValetServiceManager;
+use crate::nginx::Nginx;
+use crate::php_fpm::PhpFpm;
+use crate::services::memcached::Memcached;
+use crate::services::mysql::Mysql;
+use crate::services::postgres::Postgres;
+use crate::services::redis::Redis;
+use crate::services::registry::ServiceRegistry;
+use crate::site::Site;
+use crate::site_secure::SiteSecure;
+
+pub mod cache;
+pub mod database;
+pub mod domains;
+pub mod logs;
+pub mod mail;
+pub mod php;
+pub mod processes;
+pub mod services;
+pub mod sites;
+pub mod tunnels;
+
+// Build the services the Tauri commands operate on
+pub(crate) fn nginx() -> Nginx {
+    let cli = ValetCommandLine;
+    let files = ValetFilesystem;
+    let config = Configuration::new(files);
+    let sm = ValetServiceManager::new(cli, files);
+    let pm = Apt::new(Box::new(cli), Box::new(sm.clone()));
+    let site_secure = SiteSecure::new(files, cli, config);
+    Nginx::new(pm, Box::new(sm), cli, files, config, site_secure)
+}
+
+pub(crate) fn php_fpm() -> PhpFpm {
+    let cli = ValetCommandLine;
+    let files = ValetFilesystem;
+    let config = Configuration::new(files);
+    let sm = ValetServiceManager::new(cli, files);
+    let pm = Apt::new(Box::new(cli), Box::new(sm.clone()));
+    PhpFpm::new(config, pm, sm, cli, files, nginx())
+}
+
+pub(crate) fn site() -> Site {
+    let cli = ValetCommandLine;
+    let files = ValetFilesystem;
+    let config = Configuration::new(files);
+    Site::new(config, cli, files, php_fpm(), SiteSecure::new(files, cli, config))
+}
+
+pub(crate) fn mysql() -> Mysql {
+    let cli = ValetCommandLine;
+    let files = ValetFilesystem;
+    let sm = ValetServiceManager::new(cli, files);
+    let pm = Apt::new(Box::new(cli), Box::new(sm.clone()));
+    Mysql::new(pm, sm, cli, files, Configuration::new(files))
+}
+
+pub(crate) fn postgres() -> Postgres {
+    let cli = ValetCommandLine;
+    let files = ValetFilesystem;
+    let sm = ValetServiceManager::new(cli, files);
+    let pm = Apt::new(Box::new(cli), Box::new(sm.clone()));
+    Postgres::new(pm, sm, cli, Configuration::new(files))
+}
+
+pub(crate) fn redis() -> Redis {
+    let cli = ValetCommandLine;
+    let files = ValetFilesystem;
+    let sm = ValetServiceManager::new(cli, files);
+    let pm = Apt::new(Box::new(cli), Box::new(sm.clone()));
+    Redis::new(pm, sm, cli, files, Configuration::new(files))
+}
+
+pub(crate) fn memcached() -> Memcached {
+    let cli = ValetCommandLine;
+    let files = ValetFilesystem;
+    let sm = ValetServiceManager::new(cli, files);
+    let pm = Apt::new(Box::new(cli), Box::new(sm.clone()));
+    Memcached::new(pm, sm, files, Configuration::new(files))
+}
+
+pub(crate) fn dnsmasq() -> DnsMasq {
+    let cli = ValetCommandLine;
+    let files = ValetFilesystem;
+    let sm = ValetServiceManager::new(cli, files);
+    let pm = Apt::new(Box::new(cli), Box::new(sm.clone()));
+    DnsMasq::new(pm, sm, cli, files, Configuration::new(files))
+}
+
+pub(crate) fn mailpit() -> Mailpit {
+    let cli = ValetCommandLine;
+    let files = ValetFilesystem;
+    let sm = ValetServiceManager::new(cli, files);
+    let config = Configuration::new(files);
+    let site_secure = SiteSecure::new(files, cli, config);
+    Mailpit::new(sm, cli, files, config, site_secure, nginx(), php_fpm())
+}
+
+pub(crate) fn logs() -> Logs {
+    let files = ValetFilesystem;
+    Logs::new(files, Configuration::new(files))
+}
+
+// Every service valetui manages, in display order. Register new services here.
+pub(crate) fn registry() -> ServiceRegistry {
+    ServiceRegistry::new()
+        .register(Box::new(nginx()))
+        .register(Box::new(php_fpm()))
+        .register(Box::new(dnsmasq()))
+        .register(Box::new(mailpit()))
+        .register(Box::new(mysql()))
+        .register(Box::new(postgres()))
+        .register(Box::new(redis()))
+        .register(Box::new(memcached()))
+}
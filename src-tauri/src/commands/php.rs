@@ -0,0 +1,80 @@
+use std::collections::BTreeMap;
+
+use crate::commands::{nginx, php_fpm, site};
+use crate::fpm_status::{FpmStatus, SlowLogEntry};
+use crate::php_fpm::FpmPoolSettings;
+
+// Effective php.ini values for a PHP version, or for a site when one is given
+#[tauri::command]
+pub fn php_ini(version: Option<String>, site_name: Option<String>) -> BTreeMap<String, String> {
+    match site_name {
+        Some(name) => site().effective_php_ini(&name),
+        None => php_fpm().effective_ini(version.as_deref(), &BTreeMap::new()),
+    }
+}
+
+// Set (or clear, when value is empty) a php.ini override for a version or a site
+#[tauri::command]
+pub fn set_php_ini(version: Option<String>, site_name: Option<String>, key: String, value: Option<String>) -> Result<(), String> {
+    let value = value.filter(|v| !v.is_empty());
+    match site_name {
+        Some(name) => {
+            site().set_php_ini_override(&name, &key, value.as_deref())?;
+            nginx().restart();
+            Ok(())
+        }
+        None => php_fpm().set_ini_override(version.as_deref(), &key, value.as_deref()),
+    }
+}
+
+// FPM pool settings for a PHP version
+#[tauri::command]
+pub fn fpm_pool(version: Option<String>) -> FpmPoolSettings {
+    php_fpm().pool_settings(version.as_deref())
+}
+
+// Validate, render and apply FPM pool settings for a PHP version
+#[tauri::command]
+pub fn set_fpm_pool(version: Option<String>, settings: FpmPoolSettings) -> Result<(), String> {
+    php_fpm().set_pool_settings(version.as_deref(), settings)
+}
+
+#[tauri::command]
+pub fn enable_fpm_monitoring(version: Option<String>) -> Result<(), String> {
+    php_fpm().enable_monitoring(version.as_deref())
+}
+
+// Live process counters of a pool, read from its status page
+#[tauri::command]
+pub fn fpm_status(version: Option<String>) -> Result<FpmStatus, String> {
+    php_fpm().pool_status(version.as_deref())
+}
+
+// Slow requests of a pool with their stack traces
+#[tauri::command]
+pub fn fpm_slow_log(version: Option<String>) -> Vec<SlowLogEntry> {
+    php_fpm().slow_log(version.as_deref())
+}
+
+// Make `version` the default PHP, returns the server blocks moved to its socket (isolated sites are left alone)
+#[tauri::command]
+pub fn switch_php_version(version: String, update_cli: bool) -> Result<Vec<String>, String> {
+    let mut fpm = php_fpm();
+    let normalized = fpm.normalize_php_version(&version);
+    if !fpm.validate_version(&normalized) {
+        return Err(format!("PHP [{}] is not supported.", version));
+    }
+    fpm.switch_version(&normalized, update_cli, false)
+}
+
+// Install the per-directory `php`/`composer` shims and return the PATH line to add to the shell
+#[tauri::command]
+pub fn install_php_shims() -> Result<String, String> {
+    php_fpm().install_cli_shims()
+}
+
+// Remove the `php`/`composer` shims again
+#[tauri::command]
+pub fn uninstall_php_shims() -> Result<(), String> {
+    php_fpm().uninstall_cli_shims()
+}
@@ -0,0 +1,43 @@
+use tauri::State;
+
+use crate::commands::site;
+use crate::supervisor::{ProcessDefinition, ProcessStatus, Supervisor};
+
+fn site_path(site_name: &str) -> Result<String, String> {
+    site().site_path(site_name)
+        .ok_or(format!("The [{}] site could not be found in Valet's site list.", site_name))
+}
+
+// Processes declared in the site's .valetui.toml or Procfile
+#[tauri::command]
+pub fn process_definitions(site_name: String) -> Result<Vec<ProcessDefinition>, String> {
+    ProcessDefinition::load(&site_path(&site_name)?)
+}
+
+#[tauri::command]
+pub fn processes(supervisor: State<Supervisor>, site_name: Option<String>) -> Vec<ProcessStatus> {
+    supervisor.processes(site_name.as_deref())
+}
+
+// Start one process, or every autostart process of the site when no name is given
+#[tauri::command]
+pub fn process_start(supervisor: State<Supervisor>, site_name: String, name: Option<String>) -> Result<(), String> {
+    let path = site_path(&site_name)?;
+    match name {
+        Some(name) => supervisor.start_process(&site_name, &path, &name),
+        None => supervisor.start_site(&site_name, &path),
+    }
+}
+
+#[tauri::command]
+pub fn process_stop(supervisor: State<Supervisor>, site_name: String, name: Option<String>) {
+    match name {
+        Some(name) => supervisor.stop(&site_name, &name),
+        None => supervisor.stop_site(&site_name),
+    }
+}
+
+#[tauri::command]
+pub fn process_restart(supervisor: State<Supervisor>, site_name: String, name: String) -> Result<(), String> {
+    supervisor.restart(&site_name, &site_path(&site_name)?, &name)
+}
@@ -0,0 +1,22 @@
+use crate::commands::{mailpit, registry};
+use crate::services::ServiceInfo;
+
+// Every registered service with its state, ports and log files
+#[tauri::command]
+pub fn services() -> Vec<ServiceInfo> {
+    registry().list()
+}
+
+// Run install, uninstall, start, stop, restart or health on a service by name
+#[tauri::command]
+pub fn service_action(name: String, action: String) -> Result<(), String> {
+    registry().control(&name, &action)
+}
+
+// Serve the Mailpit inbox at https://mails.{domain} (or plain http when false)
+#[tauri::command]
+pub fn mailpit_secure(secure: bool) -> Result<String, String> {
+    let mailpit = mailpit();
+    mailpit.set_secure(secure)?;
+    Ok(mailpit.url())
+}
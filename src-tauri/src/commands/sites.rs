@@ -0,0 +1,219 @@
+use tauri::{AppHandle, Manager, State};
+
+use crate::access_log::{AccessLogEntry, AccessLogStats};
+use crate::commands::{nginx, postgres, site};
+use crate::configuration::Configuration;
+use crate::lan_share::{LanShare, LanShareInfo};
+use crate::manager::file_system::ValetFilesystem;
+use crate::paths::{Paths, PathTrait};
+use crate::site::{LinkedSite, ProxySite, Snippet};
+use crate::site_info::{SiteInfo, SiteQuery};
+use crate::site_watcher::{SiteChange, SiteWatcher};
+use crate::supervisor::Supervisor;
+
+// (Re)watch every parked path and Sites/, emitting "site-changed" events
+pub(crate) fn watch_sites(app: &AppHandle) -> Result<(), String> {
+    let mut directories = site().parked_paths();
+    directories.push(Paths::sites_path(None));
+    let handle = app.clone();
+    app.state::<SiteWatcher>().watch(directories, move |event| {
+        let site = site();
+        let changed = match event.change {
+            SiteChange::Added => site.site_appeared(&event.site),
+            SiteChange::Removed => {
+                handle.state::<Supervisor>().stop_site(&event.site);
+                site.site_vanished(&event.site)
+            }
+        };
+        if changed {
+            nginx().restart();
+        }
+        let _ = handle.emit_all("site-changed", event);
+    })
+}
+
+// The site list, filtered and sorted server side
+#[tauri::command]
+pub fn sites(query: Option<SiteQuery>) -> Vec<SiteInfo> {
+    site().sites(&query.unwrap_or_default())
+}
+
+#[tauri::command]
+pub fn site_info(site_name: String) -> Result<SiteInfo, String> {
+    site().info(&site_name, true)
+}
+
+#[tauri::command]
+pub fn site_aliases(site_name: String) -> Vec<String> {
+    site().aliases(&site_name)
+}
+
+// Replace the site's extra hostnames, its certificate is reissued to cover them
+#[tauri::command]
+pub fn set_site_aliases(site_name: String, aliases: Vec<String>) -> Result<Vec<String>, String> {
+    let aliases = site().set_aliases(&site_name, aliases)?;
+    nginx().restart();
+    Ok(aliases)
+}
+
+#[tauri::command]
+pub fn site_snippets(site_name: String) -> Vec<Snippet> {
+    site().snippets(&site_name)
+}
+
+// Save Snippets/{url}/{name}.conf, rolled back when `nginx -t` fails
+#[tauri::command]
+pub fn save_site_snippet(site_name: String, name: String, content: String) -> Result<(), String> {
+    let nginx = nginx();
+    site().save_snippet(&site_name, &name, &content, || nginx.test_configuration())?;
+    nginx.restart();
+    Ok(())
+}
+
+#[tauri::command]
+pub fn delete_site_snippet(site_name: String, name: String) -> Result<(), String> {
+    site().delete_snippet(&site_name, &name)?;
+    nginx().restart();
+    Ok(())
+}
+
+#[tauri::command]
+pub fn parked_paths() -> Vec<String> {
+    site().parked_paths()
+}
+
+#[tauri::command]
+pub fn park(app: AppHandle, path: String) -> Result<Vec<String>, String> {
+    let sites = site().park(&path)?;
+    watch_sites(&app)?;
+    Ok(sites)
+}
+
+#[tauri::command]
+pub fn unpark(app: AppHandle, watcher: State<SiteWatcher>, path: String) -> Result<(), String> {
+    // Stop watching first, so the unparked directory reports nothing while it goes away
+    watcher.unwatch();
+    let unparked = site().unpark(&path);
+    watch_sites(&app)?;
+    unparked
+}
+
+// Unpark and remove the certificates and server blocks of the sites that are no longer served
+#[tauri::command]
+pub fn forget(app: AppHandle, watcher: State<SiteWatcher>, supervisor: State<Supervisor>, path: String) -> Result<Vec<String>, String> {
+    watcher.unwatch();
+    let forgotten = match site().forget(&path) {
+        Ok(forgotten) => forgotten,
+        Err(error) => {
+            watch_sites(&app)?;
+            return Err(error);
+        }
+    };
+    for site in &forgotten {
+        supervisor.stop_site(site);
+    }
+    nginx().restart();
+    watch_sites(&app)?;
+    Ok(forgotten)
+}
+
+#[tauri::command]
+pub fn link(path: String, site_name: String) -> Result<String, String> {
+    let url = site().link(&path, &site_name)?;
+    // The link is in place either way, a missing database must not report it as failed
+    if let Err(error) = postgres().site_linked(&site_name) {
+        eprintln!("Unable to create the database of {}: {}", site_name, error);
+    }
+    Ok(url)
+}
+
+#[tauri::command]
+pub fn unlink(supervisor: State<Supervisor>, site_name: String) -> Result<(), String> {
+    site().unlink(&site_name)?;
+    supervisor.stop_site(&site_name);
+    nginx().restart();
+    Ok(())
+}
+
+#[tauri::command]
+pub fn links() -> Vec<LinkedSite> {
+    site().links()
+}
+
+#[tauri::command]
+pub fn site_access_log(site_name: String) -> bool {
+    site().access_log_enabled(&site_name)
+}
+
+#[tauri::command]
+pub fn set_site_access_log(site_name: String, enabled: bool) -> Result<(), String> {
+    let nginx = nginx();
+    if enabled {
+        nginx.ensure_access_log_format();
+    }
+    site().set_access_log(&site_name, enabled)?;
+    nginx.restart();
+    Ok(())
+}
+
+// Most recent requests of the site's access log, newest first
+#[tauri::command]
+pub fn site_requests(site_name: String, limit: Option<usize>) -> Result<Vec<AccessLogEntry>, String> {
+    site().requests(&site_name, limit.unwrap_or(200))
+}
+
+// Status code counts and the slowest endpoints of the site's access log
+#[tauri::command]
+pub fn site_request_stats(site_name: String, slowest: Option<usize>) -> Result<AccessLogStats, String> {
+    site().request_stats(&site_name, slowest.unwrap_or(10))
+}
+
+// Serve `site_name` by proxying to a local dev server such as `localhost:5173`
+#[tauri::command]
+pub fn site_proxy(site_name: String, upstream: String, secure: bool) -> Result<String, String> {
+    let url = site().proxy(&site_name, &upstream, secure)?;
+    nginx().restart();
+    Ok(url)
+}
+
+#[tauri::command]
+pub fn site_unproxy(site_name: String) -> Result<(), String> {
+    site().unproxy(&site_name)?;
+    nginx().restart();
+    Ok(())
+}
+
+#[tauri::command]
+pub fn site_proxies() -> Vec<ProxySite> {
+    site().proxies()
+}
+
+// Open the site to phones on the local network for `minutes`, "site-share-ended" fires on expiry
+#[tauri::command]
+pub fn site_share(app: AppHandle, shares: State<LanShare>, site_name: String, minutes: Option<u64>) -> Result<LanShareInfo, String> {
+    let info = site().info(&site_name, false)?;
+    let handle = app.clone();
+    let share = shares.share(&Configuration::new(ValetFilesystem), &site_name, &info.url, info.secured, minutes.unwrap_or(30), move |site| {
+        nginx().restart();
+        let _ = handle.emit_all("site-share-ended", site);
+    })?;
+    let nginx = nginx();
+    if let Err(error) = nginx.test_configuration() {
+        shares.stop(&site_name);
+        return Err(error);
+    }
+    nginx.restart();
+    Ok(share)
+}
+
+#[tauri::command]
+pub fn site_unshare(shares: State<LanShare>, site_name: String) {
+    if shares.stop(&site_name) {
+        nginx().restart();
+    }
+}
+
+#[tauri::command]
+pub fn site_shares(shares: State<LanShare>) -> Vec<LanShareInfo> {
+    shares.shares()
+}
@@ -0,0 +1,56 @@
+use tauri::State;
+
+use crate::commands::{nginx, site};
+use crate::configuration::Configuration;
+use crate::manager::file_system::ValetFilesystem;
+use crate::tunnels::{TunnelInfo, TunnelProviderInfo, TunnelSettings, Tunnels};
+
+// Built-in drivers and whether their program is installed
+#[tauri::command]
+pub fn tunnel_providers() -> Vec<TunnelProviderInfo> {
+    TunnelSettings::load(&Configuration::new(ValetFilesystem))
+        .providers()
+        .iter()
+        .map(|provider| provider.info())
+        .collect()
+}
+
+#[tauri::command]
+pub fn tunnel_settings() -> TunnelSettings {
+    TunnelSettings::load(&Configuration::new(ValetFilesystem))
+}
+
+#[tauri::command]
+pub fn set_tunnel_settings(settings: TunnelSettings) {
+    settings.save(&Configuration::new(ValetFilesystem));
+}
+
+// Publish the site through `provider` and return its public url
+#[tauri::command]
+pub fn site_tunnel_open(tunnels: State<Tunnels>, site_name: String, provider: String) -> Result<TunnelInfo, String> {
+    let config = Configuration::new(ValetFilesystem);
+    let provider = TunnelSettings::load(&config).provider(&provider)?;
+    let info = site().info(&site_name, false)?;
+    tunnels.open(&config, provider.as_ref(), &site_name, &info.url, info.secured, || {
+        let nginx = nginx();
+        nginx.test_configuration()?;
+        nginx.restart();
+        Ok(())
+    })
+}
+
+#[tauri::command]
+pub fn site_tunnel_close(tunnels: State<Tunnels>, site_name: String) {
+    if tunnels.close(&site_name) {
+        nginx().restart();
+    }
+}
+
+#[tauri::command]
+pub fn site_tunnels(tunnels: State<Tunnels>) -> Vec<TunnelInfo> {
+    let (tunnels, listeners) = tunnels.tunnels();
+    if listeners {
+        nginx().restart();
+    }
+    tunnels
+}
@@ -3,6 +3,7 @@ use serde_json::Value;
 use crate::constants::{user, Valet};
 use crate::manager::file_system::ValetFilesystem;
 use crate::manager::interface::Filesystem;
+use crate::stub::Stub;
 
 #[derive(Clone, Copy)]
 pub struct Configuration {
@@ -33,21 +34,34 @@ impl Configuration {
         }
     }
 
-    // Add the given path to the configuration
-    fn add_path(&self, path: &str, prepend: bool) {
+    // Parked paths, in the order sites are looked up
+    pub(crate) fn paths(&self) -> Vec<String> {
+        self.get("paths")
+            .and_then(|paths| paths.as_array().cloned())
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|path| path.as_str().map(|p| p.to_string()))
+            .collect()
+    }
+
+    // Add the given path to the configuration, moving it when already present
+    pub(crate) fn add_path(&self, path: &str, prepend: bool) {
         let mut config = self.read();
+        if !config["paths"].is_array() {
+            config["paths"] = Value::Array(vec![]);
+        }
         let paths = config["paths"].as_array_mut().unwrap();
+        paths.retain(|p| p != path);
         if prepend {
             paths.insert(0, Value::String(path.to_string()));
         } else {
             paths.push(Value::String(path.to_string()));
         }
-        *paths = paths.clone().into_iter().collect();
         self.write(&config);
     }
 
     // Remove the given path from the configuration
-    fn remove_path(&self, path: &str) {
+    pub(crate) fn remove_path(&self, path: &str) {
         let mut config = self.read();
         let paths = config["paths"].as_array_mut().unwrap();
         *paths = paths
@@ -60,7 +74,7 @@ impl Configuration {
     }
 
     // Prune all non-existent paths from the configuration
-    fn prune(&self) {
+    pub(crate) fn prune(&self) {
         if !self.files.exists(&self.path()) {
             return;
         }
@@ -88,18 +102,80 @@ impl Configuration {
         self.write(&config);
     }
 
-    // Parse domain based on configuration
-    fn parse_domain(&self, site_name: &str) -> String {
-        let domain = self.get("domain")
-            .and_then(|v| v.as_str().map(|s| s.to_string()))
-            .unwrap_or_else(|| "test".to_string());
-        if !site_name.ends_with(&format!(".{}", domain)) {
-            format!("{}.{}", site_name, domain)
+    // Get a per-site configuration value
+    pub(crate) fn get_site(&self, site: &str, key: &str) -> Option<Value> {
+        let config = self.read();
+        config.get("sites")
+            .and_then(|sites| sites.get(site))
+            .and_then(|settings| settings.get(key))
+            .cloned()
+    }
+
+    // Set a per-site configuration value, removing it when the value is null
+    pub(crate) fn set_site(&self, site: &str, key: &str, value: Value) {
+        let mut config = self.read();
+        if value.is_null() {
+            let settings = config.get_mut("sites")
+                .and_then(|sites| sites.get_mut(site))
+                .and_then(|settings| settings.as_object_mut());
+            if let Some(settings) = settings {
+                settings.remove(key);
+            }
         } else {
+            config["sites"][site][key] = value;
+        }
+        self.write(&config);
+    }
+
+    // The primary TLD, site urls and certificates are named after it
+    pub(crate) fn domain(&self) -> String {
+        self.get("domain")
+            .and_then(|v| v.as_str().map(|s| s.to_string()))
+            .unwrap_or_else(|| "test".to_string())
+    }
+
+    // Every TLD sites are served on, the primary one first
+    pub(crate) fn domains(&self) -> Vec<String> {
+        let domain = self.domain();
+        let mut domains = vec![domain.clone()];
+        let extra = self.get("domains")
+            .and_then(|domains| domains.as_array().cloned())
+            .unwrap_or_default();
+        for tld in extra.iter().filter_map(|tld| tld.as_str()) {
+            if !domains.iter().any(|d| d == tld) {
+                domains.push(tld.to_string());
+            }
+        }
+        domains
+    }
+
+    // Store the TLD list, the first entry becomes the primary domain
+    pub(crate) fn set_domains(&self, domains: &[String]) {
+        let Some(primary) = domains.first() else {
+            return;
+        };
+        let mut config = self.read();
+        config["domain"] = Value::String(primary.clone());
+        config["domains"] = Value::Array(domains.iter().map(|tld| Value::String(tld.clone())).collect());
+        self.write(&config);
+    }
+
+    // Parse domain based on configuration, names already ending in any served TLD are kept
+    pub(crate) fn parse_domain(&self, site_name: &str) -> String {
+        if self.domains().iter().any(|tld| site_name.ends_with(&format!(".{}", tld))) {
             site_name.to_string()
+        } else {
+            format!("{}.{}", site_name, self.domain())
         }
     }
 
+    // The site name of an url on any served TLD
+    pub(crate) fn strip_domain<'a>(&self, url: &'a str) -> &'a str {
+        self.domains().iter()
+            .find_map(|tld| url.strip_suffix(&format!(".{}", tld)))
+            .unwrap_or(url)
+    }
+
     // Update a specific key in the configuration file
     fn update_key(&self, key: &str, value: Value) {
         let mut config = self.read();
@@ -130,8 +206,7 @@ impl Configuration {
         let drivers_directory = format!("{}/Drivers", Valet::home_path());
         if !self.files.is_dir(&drivers_directory) {
             self.files.mkdir(&drivers_directory, 0o775).unwrap();
-            let sample_driver_content =
-                self.files.get(&format!("{}/stubs/SampleValetDriver.php", Valet::root_path())).unwrap();
+            let sample_driver_content = Stub::load("SampleValetDriver.php").unwrap().render().unwrap();
             self.files.put(
                 &format!("{}/SampleValetDriver.php", drivers_directory),
                 &sample_driver_content,
@@ -1,5 +1,4 @@
 use std::env;
-use std::path::Path;
 use std::process::Command;
 
 use dirs;
@@ -16,6 +15,12 @@ pub const COMMON_EXTENSIONS: [&str; 10] = [
     "cli", "mysql", "gd", "zip", "xml", "curl", "mbstring", "pgsql", "intl", "posix"
 ];
 pub const FPM_CONFIG_FILE_NAME: &str = "valet.conf";
+pub const FPM_STATUS_PATH: &str = "/valet-fpm-status";
+pub const PHP_INI_FILE_NAME: &str = "99-valetui.ini";
+pub const EDITABLE_PHP_INI_KEYS: [&str; 7] = [
+    "memory_limit", "upload_max_filesize", "post_max_size", "max_execution_time",
+    "max_input_time", "max_input_vars", "display_errors"
+];
 
 pub const NGINX_CONF: &str = "/etc/nginx/nginx.conf";
 pub const SITES_AVAILABLE_CONF: &str = "/etc/nginx/sites-available/valet.conf";
@@ -48,10 +53,6 @@ pub fn group() -> Option<String> {
 pub struct Valet;
 
 impl Valet {
-    pub fn root_path() -> String {
-        let out_dir = env!("OUT_DIR");
-        Path::new(out_dir).to_string_lossy().to_string()
-    }
     pub fn home_path() -> String {
         let dir = dirs::home_dir().unwrap();
         let config_path = dir.join(".config/valetui");
@@ -1,5 +1,21 @@
+use std::path::Path;
+
+use crate::manager::command::ValetCommandLine;
+use crate::manager::interface::CommandLine;
+
 pub struct DevTools;
 
 impl DevTools {
-    pub(crate) fn get_bin(_bin: &String, _args: &[&str]) {}
-}
\ No newline at end of file
+    // Find the first `bin` on the PATH that does not live in one of the ignored directories
+    pub(crate) fn get_bin(bin: &String, ignored_dirs: &[&str]) -> Option<String> {
+        let output = ValetCommandLine.run(&format!("which -a {}", bin)).unwrap_or_default();
+        output.lines()
+            .map(|line| line.trim())
+            .filter(|path| !path.is_empty())
+            .find(|path| {
+                let dir = Path::new(path).parent().map(|p| p.to_string_lossy().to_string()).unwrap_or_default();
+                !ignored_dirs.iter().any(|ignored| dir == ignored.trim_end_matches('/'))
+            })
+            .map(|path| path.to_string())
+    }
+}
@@ -1,17 +1,20 @@
 use std::fs::File;
 use std::io::Write;
-use crate::constants::{user, Valet};
+use crate::configuration::Configuration;
+use crate::constants::{group, user, Valet};
 use crate::manager::apt::Apt;
 use crate::manager::command::ValetCommandLine;
 use crate::manager::file_system::ValetFilesystem;
 use crate::manager::interface::{CommandLine, Filesystem, PackageManager, ServiceManager};
 use crate::manager::service_manager::ValetServiceManager;
+use crate::stub::Stub;
 
 pub struct DnsMasq {
     pm: Apt,
     sm: ValetServiceManager,
     cli: ValetCommandLine,
     files: ValetFilesystem,
+    config: Configuration,
     rclocal: String,
     resolvconf: String,
     dnsmasqconf: String,
@@ -22,12 +25,13 @@ pub struct DnsMasq {
 }
 
 impl DnsMasq {
-    pub fn new(pm: Apt, sm: ValetServiceManager, cli: ValetCommandLine, files: ValetFilesystem) -> Self {
+    pub fn new(pm: Apt, sm: ValetServiceManager, cli: ValetCommandLine, files: ValetFilesystem, config: Configuration) -> Self {
         DnsMasq {
             pm,
             sm,
             cli,
             files,
+            config,
             rclocal: "/etc/rc.local".to_string(),
             resolvconf: "/etc/resolv.conf".to_string(),
             dnsmasqconf: "/etc/dnsmasq.conf".to_string(),
@@ -38,24 +42,36 @@ impl DnsMasq {
         }
     }
 
-    pub fn install(&self, domain: &str) -> Result<(), Box<dyn std::error::Error>> {
+    pub fn install(&self, domains: &[String]) -> Result<(), Box<dyn std::error::Error>> {
         self.dnsmasq_setup().unwrap();
         self.stop_resolved();
-        self.create_custom_config_file(domain).unwrap();
+        self.create_custom_config_file(domains).unwrap();
         self.sm.restart(vec!["dnsmasq"]);
         Ok(())
     }
 
+    pub fn start(&self) {
+        self.sm.start(vec!["dnsmasq"])
+    }
+
+    pub fn domains(&self) -> Vec<String> {
+        self.config.domains()
+    }
+
     pub fn stop(&self) {
         self.sm.stop(vec!["dnsmasq"])
     }
 
+    pub fn is_running(&self) -> bool {
+        self.sm.is_running("dnsmasq")
+    }
+
     pub fn restart(&self) {
         self.sm.restart(vec!["dnsmasq"])
     }
 
-    pub fn update_domain(&self, new_domain: &str) {
-        self.create_custom_config_file(new_domain).unwrap();
+    pub fn update_domains(&self, domains: &[String]) {
+        self.create_custom_config_file(domains).unwrap();
         self.sm.restart(vec!["dnsmasq"])
     }
 
@@ -105,12 +121,35 @@ impl DnsMasq {
         Ok(())
     }
 
-    fn create_custom_config_file(&self, domain: &str) -> Result<(), Box<dyn std::error::Error>> {
+    // One address line per TLD, every name below it resolves to this machine
+    fn create_custom_config_file(&self, domains: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+        let addresses: String = domains.iter().map(|domain| format!("address=/.{}/127.0.0.1\n", domain)).collect();
+        self.prepare_log_file()?;
         let mut file = File::create(&self.config_path).unwrap();
-        file.write_all(format!("address=/{}.127.0.0.1\nserver=1.1.1.1\nserver=8.8.8.8\n", domain).as_bytes()).unwrap();
+        file.write_all(format!(
+            "{}server=1.1.1.1\nserver=8.8.8.8\nlog-facility={}\n",
+            addresses,
+            Self::log_file()
+        ).as_bytes()).unwrap();
         Ok(())
     }
 
+    // dnsmasq opens the log as root and hands it to its own user, the group keeps it readable for the desktop user
+    fn prepare_log_file(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let path = Self::log_file();
+        self.files.ensure_dir_exists(&format!("{}/Log", Valet::home_path()), &user(), 0o755)?;
+        self.files.touch(&path)?;
+        if let Some(group) = group() {
+            self.cli.run(&format!("chown {}:{} \"{}\"", user(), group, path))?;
+        }
+        self.files.chmod(&path, 0o664)?;
+        Ok(())
+    }
+
+    pub fn log_file() -> String {
+        format!("{}/Log/dnsmasq.log", Valet::home_path())
+    }
+
     fn stop_resolved(&self) {
         if !self.sm.disabled("systemd-resolved") {
             self.sm.disable("systemd-resolved");
@@ -132,13 +171,9 @@ impl DnsMasq {
         self.files.unlink("/etc/dnsmasq.d/network-manager").unwrap();
         self.files.backup(&self.dnsmasqconf).unwrap();
 
-        let dnsmasq_conf_stub = format!("{}/stubs/dnsmasq.conf.stub", Valet::root_path());
-        let dnsmasq_opts_stub = format!("{}/stubs/dnsmasq_options.stub", Valet::root_path());
-        let nm_config_stub = format!("{}/stubs/networkmanager.conf.stub", Valet::root_path());
-
-        self.files.put(&self.dnsmasqconf, dnsmasq_conf_stub.as_str()).unwrap();
-        self.files.put(&self.dnsmasq_opts, dnsmasq_opts_stub.as_str()).unwrap();
-        self.files.put(&self.nm_config_path, nm_config_stub.as_str()).unwrap();
+        self.files.put(&self.dnsmasqconf, &Stub::load("dnsmasq.conf")?.render()?).unwrap();
+        self.files.put(&self.dnsmasq_opts, &Stub::load("dnsmasq_options")?.render()?).unwrap();
+        self.files.put(&self.nm_config_path, &Stub::load("networkmanager.conf")?.render()?).unwrap();
         Ok(())
     }
 }
\ No newline at end of file
@@ -0,0 +1,179 @@
+use regex::Regex;
+use serde::Serialize;
+
+use crate::configuration::Configuration;
+use crate::constants::Valet;
+use crate::dnsmasq::DnsMasq;
+use crate::mailpit::Mailpit;
+use crate::manager::file_system::ValetFilesystem;
+use crate::manager::interface::Filesystem;
+use crate::nginx::Nginx;
+use crate::paths::{Paths, PathTrait};
+use crate::site_secure::SiteSecure;
+
+// Browsers force https on these through the HSTS preload list, so plain http sites break
+const HSTS_PRELOADED: &[&str] = &[
+    "android", "app", "bank", "boo", "channel", "chrome", "dad", "day", "dev", "eat", "esq", "fly", "foo", "gle",
+    "gmail", "google", "hangout", "how", "ing", "insurance", "meme", "mov", "new", "nexus", "page", "phd", "prof",
+    "rsvp", "search", "soy", "youtube", "zip",
+];
+
+// Delegated TLDs people tend to pick, their real hosts become unreachable
+const REAL_TLDS: &[&str] = &[
+    "ai", "biz", "ca", "co", "com", "de", "dk", "es", "eu", "fr", "info", "io", "it", "me", "net", "nl", "org",
+    "ru", "site", "tech", "tv", "uk", "us", "xyz",
+];
+
+#[derive(Serialize, Clone, Debug)]
+pub struct DomainCheck {
+    pub domain: String,
+    // Shown to the user, the change needs to be forced while it is set
+    pub warning: Option<String>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct DomainProgress {
+    pub step: usize,
+    pub total: usize,
+    pub message: String,
+}
+
+// Changes the primary TLD: config, DNS, server blocks, certificates and Mailpit, rolled back when a step fails or nginx rejects the result
+pub struct DomainChange {
+    files: ValetFilesystem,
+    config: Configuration,
+    site_secure: SiteSecure,
+    dnsmasq: DnsMasq,
+    mailpit: Mailpit,
+    nginx: Nginx,
+}
+
+impl DomainChange {
+    const STEPS: usize = 7;
+
+    pub fn new(files: ValetFilesystem, config: Configuration, site_secure: SiteSecure, dnsmasq: DnsMasq, mailpit: Mailpit, nginx: Nginx) -> Self {
+        DomainChange { files, config, site_secure, dnsmasq, mailpit, nginx }
+    }
+
+    pub fn check(domain: &str) -> Result<DomainCheck, String> {
+        let domain = domain.trim().trim_start_matches('.').to_lowercase();
+        if !Regex::new(r"^[a-z0-9]([a-z0-9\-]*[a-z0-9])?$").unwrap().is_match(&domain) {
+            return Err(format!("Invalid TLD [{}].", domain));
+        }
+        let warning = if HSTS_PRELOADED.contains(&domain.as_str()) {
+            Some(format!("Browsers only load .{} sites over https, every site would need to be secured.", domain))
+        } else if domain == "local" {
+            Some("The .local TLD is used by mDNS and conflicts with Avahi.".to_string())
+        } else if REAL_TLDS.contains(&domain.as_str()) {
+            Some(format!(".{} is a real TLD, public sites using it would no longer resolve.", domain))
+        } else {
+            None
+        };
+        Ok(DomainCheck { domain, warning })
+    }
+
+    pub fn set_domain<F>(&self, domain: &str, force: bool, on_progress: F) -> Result<(), String>
+    where
+        F: Fn(DomainProgress),
+    {
+        let progress = |step: usize, message: &str| on_progress(DomainProgress {
+            step,
+            total: Self::STEPS,
+            message: message.to_string(),
+        });
+
+        progress(1, "Validating the TLD");
+        let check = Self::check(domain)?;
+        if let Some(warning) = check.warning.filter(|_| !force) {
+            return Err(warning);
+        }
+        let old_domain = self.config.domain();
+        if check.domain == old_domain {
+            return Ok(());
+        }
+        let old_domains = self.config.domains();
+
+        progress(2, "Backing up the configuration");
+        self.backup().map_err(|e| format!("Unable to back up the configuration: {}", e))?;
+
+        progress(3, "Updating the configuration");
+        let domains: Vec<String> = std::iter::once(check.domain.clone())
+            .chain(old_domains.iter().skip(1).filter(|tld| **tld != check.domain).cloned())
+            .collect();
+        self.config.set_domains(&domains);
+
+        progress(4, "Updating server blocks and certificates");
+        let old_mailpit = format!("mails.{}", old_domain);
+        let has_mailpit = self.files.exists(&Paths::nginx_path(Some(&old_mailpit)));
+        if has_mailpit {
+            if let Err(error) = self.mailpit.remove_site(&old_mailpit) {
+                self.rollback(&old_domains);
+                return Err(format!("Unable to remove the Mailpit site, the change was rolled back: {}", error));
+            }
+        }
+        if let Err(error) = self.site_secure.update_domains(&old_domain) {
+            self.rollback(&old_domains);
+            return Err(format!("Unable to update the server blocks and certificates, the change was rolled back: {}", error));
+        }
+
+        progress(5, "Updating Mailpit");
+        if has_mailpit {
+            if let Err(error) = self.mailpit.update_domain() {
+                self.rollback(&old_domains);
+                return Err(format!("Unable to update Mailpit, the change was rolled back: {}", error));
+            }
+        }
+
+        progress(6, "Updating DNS");
+        self.dnsmasq.update_domains(&domains);
+
+        progress(7, "Reloading nginx");
+        if let Err(error) = self.nginx.test_configuration() {
+            self.rollback(&old_domains);
+            return Err(format!("nginx rejected the new configuration, the change was rolled back: {}", error));
+        }
+        self.nginx.restart();
+        self.files.remove(&[&self.backup_path(None)]).map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    // Copies of config.json, Nginx/, Certificates/ and Snippets/ to restore when the change fails
+    fn backup(&self) -> std::io::Result<()> {
+        let _ = self.files.remove(&[&self.backup_path(None)]);
+        self.files.copy_directory(&Paths::nginx_path(None), &self.backup_path(Some("Nginx")))?;
+        if self.files.is_dir(&Paths::certificates_path(None)) {
+            self.files.copy_directory(&Paths::certificates_path(None), &self.backup_path(Some("Certificates")))?;
+        }
+        if self.files.is_dir(&Paths::snippets_path(None)) {
+            self.files.copy_directory(&Paths::snippets_path(None), &self.backup_path(Some("Snippets")))?;
+        }
+        std::fs::copy(Self::config_path(), self.backup_path(Some("config.json")))?;
+        Ok(())
+    }
+
+    fn rollback(&self, old_domains: &[String]) {
+        for directory in ["Nginx", "Certificates", "Snippets"] {
+            let backup = self.backup_path(Some(directory));
+            if self.files.is_dir(&backup) {
+                let current = format!("{}/{}", Valet::home_path(), directory);
+                let _ = self.files.remove(&[&current]);
+                let _ = self.files.copy_directory(&backup, &current);
+            }
+        }
+        let _ = std::fs::copy(self.backup_path(Some("config.json")), Self::config_path());
+        self.dnsmasq.update_domains(old_domains);
+        self.nginx.restart();
+    }
+
+    fn backup_path(&self, file: Option<&str>) -> String {
+        let path = format!("{}/.domain-backup", Valet::home_path());
+        match file {
+            Some(file) => format!("{}/{}", path, file),
+            None => path,
+        }
+    }
+
+    fn config_path() -> String {
+        format!("{}/config.json", Valet::home_path())
+    }
+}
@@ -0,0 +1,115 @@
+use std::error::Error;
+use std::io;
+use std::io::{Read, Write};
+use std::os::unix::net::UnixStream;
+use std::time::Duration;
+
+const FCGI_VERSION: u8 = 1;
+const FCGI_BEGIN_REQUEST: u8 = 1;
+const FCGI_END_REQUEST: u8 = 3;
+const FCGI_PARAMS: u8 = 4;
+const FCGI_STDIN: u8 = 5;
+const FCGI_STDOUT: u8 = 6;
+const FCGI_STDERR: u8 = 7;
+const FCGI_RESPONDER: u16 = 1;
+const REQUEST_ID: u16 = 1;
+
+// Minimal FastCGI client talking to a PHP-FPM pool over its Unix socket
+pub struct FastCgiClient {
+    socket: String,
+}
+
+impl FastCgiClient {
+    pub fn new(socket: &str) -> Self {
+        Self { socket: socket.to_string() }
+    }
+
+    // Send a GET request with the given params and return the response body
+    pub fn get(&self, script: &str, query: &str) -> Result<String, Box<dyn Error>> {
+        let params = [
+            ("GATEWAY_INTERFACE", "FastCGI/1.0"),
+            ("REQUEST_METHOD", "GET"),
+            ("SCRIPT_NAME", script),
+            ("SCRIPT_FILENAME", script),
+            ("REQUEST_URI", script),
+            ("QUERY_STRING", query),
+            ("SERVER_SOFTWARE", "valetui"),
+            ("SERVER_PROTOCOL", "HTTP/1.1"),
+            ("CONTENT_LENGTH", "0"),
+        ];
+        let response = self.request(&params)?;
+        // Strip the CGI headers that precede the body
+        match response.split_once("\r\n\r\n") {
+            Some((_, body)) => Ok(body.to_string()),
+            None => Ok(response),
+        }
+    }
+
+    pub fn request(&self, params: &[(&str, &str)]) -> Result<String, Box<dyn Error>> {
+        let mut stream = UnixStream::connect(&self.socket)?;
+        stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+        stream.set_write_timeout(Some(Duration::from_secs(5)))?;
+
+        let mut begin = Vec::with_capacity(8);
+        begin.extend_from_slice(&FCGI_RESPONDER.to_be_bytes());
+        begin.extend_from_slice(&[0; 6]);
+        stream.write_all(&Self::record(FCGI_BEGIN_REQUEST, &begin))?;
+
+        let mut encoded = Vec::new();
+        for (name, value) in params {
+            Self::encode_length(&mut encoded, name.len());
+            Self::encode_length(&mut encoded, value.len());
+            encoded.extend_from_slice(name.as_bytes());
+            encoded.extend_from_slice(value.as_bytes());
+        }
+        for chunk in encoded.chunks(u16::MAX as usize) {
+            stream.write_all(&Self::record(FCGI_PARAMS, chunk))?;
+        }
+        stream.write_all(&Self::record(FCGI_PARAMS, &[]))?;
+        stream.write_all(&Self::record(FCGI_STDIN, &[]))?;
+
+        let mut stdout = Vec::new();
+        let mut stderr = Vec::new();
+        loop {
+            let mut header = [0u8; 8];
+            stream.read_exact(&mut header)?;
+            let record_type = header[1];
+            let content_length = u16::from_be_bytes([header[4], header[5]]) as usize;
+            let padding_length = header[6] as usize;
+            let mut content = vec![0u8; content_length + padding_length];
+            stream.read_exact(&mut content)?;
+            content.truncate(content_length);
+            match record_type {
+                FCGI_STDOUT => stdout.extend_from_slice(&content),
+                FCGI_STDERR => stderr.extend_from_slice(&content),
+                FCGI_END_REQUEST => break,
+                _ => {}
+            }
+        }
+
+        if stdout.is_empty() && !stderr.is_empty() {
+            return Err(Box::new(io::Error::other(String::from_utf8_lossy(&stderr).to_string())));
+        }
+        Ok(String::from_utf8_lossy(&stdout).to_string())
+    }
+
+    fn record(record_type: u8, content: &[u8]) -> Vec<u8> {
+        let mut record = Vec::with_capacity(8 + content.len());
+        record.push(FCGI_VERSION);
+        record.push(record_type);
+        record.extend_from_slice(&REQUEST_ID.to_be_bytes());
+        record.extend_from_slice(&(content.len() as u16).to_be_bytes());
+        record.push(0);
+        record.push(0);
+        record.extend_from_slice(content);
+        record
+    }
+
+    fn encode_length(buffer: &mut Vec<u8>, length: usize) {
+        if length < 128 {
+            buffer.push(length as u8);
+        } else {
+            buffer.extend_from_slice(&((length as u32) | 0x8000_0000).to_be_bytes());
+        }
+    }
+}
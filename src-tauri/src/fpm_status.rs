@@ -0,0 +1,91 @@
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+// Pool status as reported by `pm.status_path?json`
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct FpmStatus {
+    #[serde(default)]
+    pub pool: String,
+    #[serde(default, alias = "process manager")]
+    pub process_manager: String,
+    #[serde(default, alias = "start since")]
+    pub start_since: u64,
+    #[serde(default, alias = "accepted conn")]
+    pub accepted_conn: u64,
+    #[serde(default, alias = "listen queue")]
+    pub listen_queue: u64,
+    #[serde(default, alias = "max listen queue")]
+    pub max_listen_queue: u64,
+    #[serde(default, alias = "listen queue len")]
+    pub listen_queue_len: u64,
+    #[serde(default, alias = "idle processes")]
+    pub idle_processes: u64,
+    #[serde(default, alias = "active processes")]
+    pub active_processes: u64,
+    #[serde(default, alias = "total processes")]
+    pub total_processes: u64,
+    #[serde(default, alias = "max active processes")]
+    pub max_active_processes: u64,
+    #[serde(default, alias = "max children reached")]
+    pub max_children_reached: u64,
+    #[serde(default, alias = "slow requests")]
+    pub slow_requests: u64,
+}
+
+impl FpmStatus {
+    pub fn parse(body: &str) -> Result<Self, String> {
+        serde_json::from_str(body.trim()).map_err(|e| format!("Unable to parse PHP-FPM status: {}", e))
+    }
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct SlowLogFrame {
+    pub function: String,
+    pub file: String,
+    pub line: u32,
+}
+
+// One slow request dumped by PHP-FPM, with the script and its stack trace
+#[derive(Serialize, Clone, Debug)]
+pub struct SlowLogEntry {
+    pub time: String,
+    pub pool: String,
+    pub pid: u32,
+    pub script: String,
+    pub trace: Vec<SlowLogFrame>,
+}
+
+impl SlowLogEntry {
+    pub fn parse_log(content: &str) -> Vec<SlowLogEntry> {
+        let header = Regex::new(r"^\[(?<time>[^\]]+)\]\s+\[pool (?<pool>[^\]]+)\] pid (?<pid>\d+)").unwrap();
+        let frame = Regex::new(r"^\[0x[0-9a-f]+\] (?<function>.+?) (?<file>\S+):(?<line>\d+)$").unwrap();
+        let mut entries: Vec<SlowLogEntry> = Vec::new();
+
+        for line in content.lines() {
+            let line = line.trim_end();
+            if let Some(caps) = header.captures(line) {
+                entries.push(SlowLogEntry {
+                    time: caps["time"].to_string(),
+                    pool: caps["pool"].to_string(),
+                    pid: caps["pid"].parse().unwrap_or_default(),
+                    script: String::new(),
+                    trace: Vec::new(),
+                });
+                continue;
+            }
+            let Some(entry) = entries.last_mut() else {
+                continue;
+            };
+            if let Some(script) = line.strip_prefix("script_filename = ") {
+                entry.script = script.to_string();
+            } else if let Some(caps) = frame.captures(line) {
+                entry.trace.push(SlowLogFrame {
+                    function: caps["function"].to_string(),
+                    file: caps["file"].to_string(),
+                    line: caps["line"].parse().unwrap_or_default(),
+                });
+            }
+        }
+        entries
+    }
+}
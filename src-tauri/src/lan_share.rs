@@ -0,0 +1,190 @@
+use std::collections::HashMap;
+use std::net::{IpAddr, TcpListener, UdpSocket};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use chrono::Local;
+use qrcode::render::svg;
+use qrcode::QrCode;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::configuration::Configuration;
+use crate::constants::Valet;
+use crate::manager::file_system::ValetFilesystem;
+use crate::manager::interface::Filesystem;
+use crate::paths::{Paths, PathTrait};
+use crate::stub::Stub;
+
+#[derive(Serialize, Clone, Debug)]
+pub struct LanShareInfo {
+    pub site: String,
+    pub url: String,
+    // What a phone on the same network opens, e.g. http://192.168.1.10:8100
+    pub share_url: String,
+    pub address: String,
+    pub port: u16,
+    pub expires_at: String,
+    // SVG markup of the share url
+    pub qr_code: String,
+}
+
+struct Sharing {
+    // Tells a share apart from a later one of the same site
+    id: u64,
+    info: LanShareInfo,
+    cancel: Arc<AtomicBool>,
+}
+
+// Exposes sites on the machine's LAN address through an extra nginx listener, torn down after a timeout
+#[derive(Default)]
+pub struct LanShare {
+    shares: Arc<Mutex<HashMap<String, Sharing>>>,
+}
+
+impl LanShare {
+    const PORTS: std::ops::RangeInclusive<u16> = 8100..=8199;
+
+    // The address other devices reach this machine on, from the route to a public IP (nothing is sent)
+    pub fn lan_address() -> Result<IpAddr, String> {
+        let socket = UdpSocket::bind("0.0.0.0:0").map_err(|e| e.to_string())?;
+        socket.connect("1.1.1.1:80").map_err(|_| "No network connection to share on.".to_string())?;
+        let address = socket.local_addr().map_err(|e| e.to_string())?.ip();
+        if address.is_loopback() || address.is_unspecified() {
+            return Err("No LAN address found to share on.".to_string());
+        }
+        Ok(address)
+    }
+
+    // Share `url` for `minutes`, `on_end` gets the site once the share expired on its own
+    pub fn share<F>(&self, config: &Configuration, site: &str, url: &str, secured: bool, minutes: u64, on_end: F) -> Result<LanShareInfo, String>
+    where
+        F: Fn(String) + Send + 'static,
+    {
+        self.stop(site);
+        let address = Self::lan_address()?;
+        let port = self.free_port(address)?;
+        let share_host = format!("{}:{}", address, port);
+        let share_url = format!("http://{}", share_host);
+        let conf = Self::listener_config(config, url, secured, &share_host, &share_url)?;
+        ValetFilesystem.put(&Self::config_path(url), &conf).map_err(|e| e.to_string())?;
+
+        let qr_code = QrCode::new(share_url.as_bytes()).map_err(|e| e.to_string())?
+            .render::<svg::Color>()
+            .min_dimensions(240, 240)
+            .build();
+        let info = LanShareInfo {
+            site: site.to_string(),
+            url: url.to_string(),
+            share_url,
+            address: address.to_string(),
+            port,
+            expires_at: (Local::now() + chrono::Duration::minutes(minutes as i64)).to_rfc3339(),
+            qr_code,
+        };
+
+        static SEQUENCE: AtomicU64 = AtomicU64::new(0);
+        let id = SEQUENCE.fetch_add(1, Ordering::SeqCst);
+        let cancel = Arc::new(AtomicBool::new(false));
+        // Registered before the expiry thread starts, so it always finds its own share
+        self.shares.lock().unwrap().insert(site.to_string(), Sharing { id, info: info.clone(), cancel: cancel.clone() });
+        {
+            let shares = self.shares.clone();
+            let site = site.to_string();
+            let url = url.to_string();
+            let expires = Instant::now() + Duration::from_secs(minutes * 60);
+            thread::spawn(move || {
+                while Instant::now() < expires {
+                    if cancel.load(Ordering::SeqCst) {
+                        return;
+                    }
+                    thread::sleep(Duration::from_secs(1));
+                }
+                let mut shares = shares.lock().unwrap();
+                // Stopped, or replaced by a newer share of the site in the meantime
+                if shares.get(&site).is_none_or(|sharing| sharing.id != id) {
+                    return;
+                }
+                shares.remove(&site);
+                let _ = ValetFilesystem.unlink(&Self::config_path(&url));
+                // Still holding the lock, so a new share of the site is only registered after on_end
+                on_end(site);
+                drop(shares);
+            });
+        }
+        Ok(info)
+    }
+
+    // False when the site was not shared, so nginx does not need a restart
+    pub fn stop(&self, site: &str) -> bool {
+        let Some(sharing) = self.shares.lock().unwrap().remove(site) else {
+            return false;
+        };
+        sharing.cancel.store(true, Ordering::SeqCst);
+        let _ = ValetFilesystem.unlink(&Self::config_path(&sharing.info.url));
+        true
+    }
+
+    // Used when valetui exits, listeners must not outlive the app
+    pub fn stop_all(&self) -> bool {
+        let sites: Vec<String> = self.shares.lock().unwrap().keys().cloned().collect();
+        let mut stopped = false;
+        for site in sites {
+            stopped |= self.stop(&site);
+        }
+        stopped
+    }
+
+    pub fn shares(&self) -> Vec<LanShareInfo> {
+        let mut shares: Vec<LanShareInfo> = self.shares.lock().unwrap().values().map(|sharing| sharing.info.clone()).collect();
+        shares.sort_by(|a, b| a.site.cmp(&b.site));
+        shares
+    }
+
+    fn free_port(&self, address: IpAddr) -> Result<u16, String> {
+        let taken: Vec<u16> = self.shares.lock().unwrap().values().map(|sharing| sharing.info.port).collect();
+        Self::PORTS
+            .filter(|port| !taken.contains(port))
+            .find(|port| TcpListener::bind((address, *port)).is_ok())
+            .ok_or("No free port left to share on.".to_string())
+    }
+
+    // Server block listening on `address` that forwards to the site with its own Host header,
+    // rewriting absolute links to `public_url`. Also used by tunnels that cannot set the Host header.
+    pub(crate) fn listener_config(config: &Configuration, url: &str, secured: bool, address: &str, public_url: &str) -> Result<String, String> {
+        let upstream = if secured {
+            format!("https://127.0.0.1:{}", Self::port(config, "https_port", 443))
+        } else {
+            format!("http://127.0.0.1:{}", Self::port(config, "port", 80))
+        };
+        Self::listener(url, secured, address, &upstream, public_url)
+    }
+
+    // The listener itself, `upstream` is where nginx serves the site
+    pub(crate) fn listener(url: &str, secured: bool, address: &str, upstream: &str, public_url: &str) -> Result<String, String> {
+        Stub::load("share.valet.conf")?
+            .set("HOME_PATH", &Valet::home_path())
+            .set("SHARE_ADDRESS", address)
+            .set("SHARE_UPSTREAM", upstream)
+            .set("SHARE_URL", public_url)
+            .set("SITE", url)
+            .flag("secure", secured)
+            .render()
+    }
+
+    pub(crate) fn port(config: &Configuration, key: &str, default: u16) -> u16 {
+        config.get(key)
+            .and_then(|value| match value {
+                Value::String(port) => port.parse().ok(),
+                Value::Number(port) => port.as_u64().map(|port| port as u16),
+                _ => None,
+            })
+            .unwrap_or(default)
+    }
+
+    fn config_path(url: &str) -> String {
+        Paths::nginx_path(Some(&format!("{}.share", url)))
+    }
+}
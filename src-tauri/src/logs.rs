@@ -0,0 +1,339 @@
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use chrono::NaiveDateTime;
+use lazy_static::lazy_static;
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::configuration::Configuration;
+use crate::constants::Valet;
+use crate::manager::file_system::ValetFilesystem;
+use crate::manager::interface::Filesystem;
+use crate::services::registry::ServiceRegistry;
+
+lazy_static! {
+    // 2024/06/01 12:00:00 [error] 1234#1234: *5 message, client: 127.0.0.1, server: app.test, request: "GET / HTTP/1.1"
+    static ref NGINX_LINE: Regex = Regex::new(r"^(\d{4}/\d{2}/\d{2} \d{2}:\d{2}:\d{2}) \[(\w+)\] \d+#\d+: (?:\*\d+ )?(.*)$").unwrap();
+    // [01-Jun-2024 12:00:00] WARNING: [pool valet] ... and [01-Jun-2024 12:00:00 UTC] PHP Fatal error:  ...
+    static ref PHP_LINE: Regex = Regex::new(r"^\[(\d{2}-\w{3}-\d{4} \d{2}:\d{2}:\d{2})(?: [\w/+-]+)?\] (?:PHP )?([A-Za-z][A-Za-z ]*?):\s+(.*)$").unwrap();
+    static ref NGINX_CONTEXT: Regex = Regex::new(r#", (client|server|request|upstream|host): "?([^",]*)"?"#).unwrap();
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Debug,
+    Info,
+    Notice,
+    Warning,
+    Error,
+    Critical,
+}
+
+impl Severity {
+    // Map nginx, PHP and PHP-FPM level names onto one scale
+    pub fn parse(level: &str) -> Self {
+        match level.to_lowercase().as_str() {
+            "debug" => Severity::Debug,
+            "notice" | "deprecated" | "strict standards" => Severity::Notice,
+            "warn" | "warning" => Severity::Warning,
+            "error" | "recoverable fatal error" => Severity::Error,
+            "crit" | "alert" | "emerg" | "fatal error" | "parse error" => Severity::Critical,
+            _ => Severity::Info,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum LogKind {
+    Nginx,
+    Site,
+    Fpm,
+    Dnsmasq,
+    Mailpit,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct LogSource {
+    pub id: String,
+    pub label: String,
+    pub kind: LogKind,
+    pub site: Option<String>,
+    pub path: String,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct LogEntry {
+    pub source: String,
+    pub time: Option<String>,
+    pub level: Severity,
+    pub message: String,
+    pub site: Option<String>,
+    pub client: Option<String>,
+    pub request: Option<String>,
+    pub upstream: Option<String>,
+    pub raw: String,
+}
+
+#[derive(Deserialize, Clone, Default, Debug)]
+#[serde(default)]
+pub struct LogFilter {
+    pub site: Option<String>,
+    // Minimum severity to keep
+    pub level: Option<Severity>,
+    pub query: Option<String>,
+}
+
+impl LogFilter {
+    pub fn matches(&self, entry: &LogEntry) -> bool {
+        if let Some(site) = &self.site {
+            if entry.site.as_ref() != Some(site) {
+                return false;
+            }
+        }
+        if let Some(level) = self.level {
+            if entry.level < level {
+                return false;
+            }
+        }
+        if let Some(query) = self.query.as_ref().filter(|q| !q.is_empty()) {
+            if !entry.raw.to_lowercase().contains(&query.to_lowercase()) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+impl LogEntry {
+    // Parse one line of an nginx or PHP(-FPM) log, falling back to the raw text.
+    // `domains` are the TLDs stripped from nginx server names to find the site.
+    pub fn parse(source: &LogSource, line: &str, domains: &[String]) -> Self {
+        let mut entry = LogEntry {
+            source: source.id.clone(),
+            time: None,
+            level: Severity::Info,
+            message: line.to_string(),
+            site: source.site.clone(),
+            client: None,
+            request: None,
+            upstream: None,
+            raw: line.to_string(),
+        };
+
+        if let Some(captures) = NGINX_LINE.captures(line) {
+            entry.time = Self::normalize_time(&captures[1], "%Y/%m/%d %H:%M:%S");
+            entry.level = Severity::parse(&captures[2]);
+            let message = &captures[3];
+            entry.message = NGINX_CONTEXT.find(message).map_or(message, |m| &message[..m.start()]).to_string();
+            for context in NGINX_CONTEXT.captures_iter(message) {
+                let value = Some(context[2].to_string());
+                match &context[1] {
+                    "client" => entry.client = value,
+                    "request" => entry.request = value,
+                    "upstream" => entry.upstream = value,
+                    "server" if entry.site.is_none() => {
+                        entry.site = domains.iter()
+                            .find_map(|tld| context[2].strip_suffix(&format!(".{}", tld)))
+                            .map(|s| s.to_string());
+                    }
+                    _ => {}
+                }
+            }
+        } else if let Some(captures) = PHP_LINE.captures(line) {
+            entry.time = Self::normalize_time(&captures[1], "%d-%b-%Y %H:%M:%S");
+            entry.level = Severity::parse(&captures[2]);
+            entry.message = captures[3].trim().to_string();
+        }
+        entry
+    }
+
+    fn normalize_time(time: &str, format: &str) -> Option<String> {
+        NaiveDateTime::parse_from_str(time, format)
+            .ok()
+            .map(|time| time.format("%Y-%m-%d %H:%M:%S").to_string())
+    }
+}
+
+pub struct Logs {
+    files: ValetFilesystem,
+    config: Configuration,
+}
+
+impl Logs {
+    // Only the end of big files is read when tailing
+    const TAIL_BYTES_PER_LINE: u64 = 512;
+
+    pub fn new(files: ValetFilesystem, config: Configuration) -> Self {
+        Logs { files, config }
+    }
+
+    pub fn domains(&self) -> Vec<String> {
+        self.config.domains()
+    }
+
+    // Every log file valetui knows about that exists on disk and can be read
+    pub fn sources(&self, services: &ServiceRegistry) -> Vec<LogSource> {
+        let mut sources: Vec<LogSource> = Vec::new();
+        for service in services.all() {
+            let kind = match service.name() {
+                "nginx" => LogKind::Nginx,
+                "php-fpm" => LogKind::Fpm,
+                "dnsmasq" => LogKind::Dnsmasq,
+                "mailpit" => LogKind::Mailpit,
+                _ => continue,
+            };
+            for path in service.log_files() {
+                // Missing or root-only, e.g. /var/log/php8.2-fpm.log, tailing them would only fail
+                if File::open(&path).is_err() {
+                    continue;
+                }
+                let file = Path::new(&path).file_name().unwrap().to_string_lossy().to_string();
+                sources.push(LogSource {
+                    id: format!("{}:{}", service.name(), file),
+                    label: format!("{} ({})", service.label(), file),
+                    kind,
+                    site: None,
+                    path,
+                });
+            }
+        }
+
+        let log_directory = format!("{}/Log", Valet::home_path());
+        let mut site_logs: Vec<String> = self.files.scandir(&log_directory).unwrap_or_default()
+            .into_iter()
+            .filter(|file| file != "nginx-error.log" && file.ends_with("-error.log"))
+            .collect();
+        site_logs.sort();
+        for file in site_logs {
+            let site = file.trim_end_matches("-error.log").to_string();
+            sources.push(LogSource {
+                id: format!("site:{}", site),
+                label: site.clone(),
+                kind: LogKind::Site,
+                site: Some(self.config.strip_domain(&site).to_string()),
+                path: format!("{}/{}", log_directory, file),
+            });
+        }
+        sources
+    }
+
+    pub fn source(&self, services: &ServiceRegistry, id: &str) -> Result<LogSource, String> {
+        self.sources(services)
+            .into_iter()
+            .find(|source| source.id == id)
+            .ok_or(format!("Unknown log [{}].", id))
+    }
+
+    // The last `lines` entries of a log that pass the filter, oldest first
+    pub fn tail(&self, source: &LogSource, lines: usize, filter: &LogFilter) -> Result<Vec<LogEntry>, String> {
+        let mut file = File::open(&source.path).map_err(|e| format!("Unable to read {}: {}", source.path, e))?;
+        let length = file.metadata().map_err(|e| e.to_string())?.len();
+        let start = length.saturating_sub(lines as u64 * Self::TAIL_BYTES_PER_LINE * 4);
+        file.seek(SeekFrom::Start(start)).map_err(|e| e.to_string())?;
+        let mut contents = Vec::new();
+        file.read_to_end(&mut contents).map_err(|e| e.to_string())?;
+        let contents = String::from_utf8_lossy(&contents);
+
+        let domains = self.domains();
+        let mut entries: Vec<LogEntry> = contents.lines()
+            // The first line is likely cut in half when we did not start at the beginning
+            .skip(if start > 0 { 1 } else { 0 })
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| LogEntry::parse(source, line, &domains))
+            .filter(|entry| filter.matches(entry))
+            .collect();
+        let skip = entries.len().saturating_sub(lines);
+        entries.drain(..skip);
+        Ok(entries)
+    }
+}
+
+// Follows log files with inotify and hands every new line to a callback
+#[derive(Default)]
+pub struct LogWatcher {
+    watcher: Mutex<Option<RecommendedWatcher>>,
+}
+
+impl LogWatcher {
+    pub fn watch<F>(&self, sources: Vec<LogSource>, filter: LogFilter, domains: Vec<String>, on_entry: F) -> Result<(), String>
+    where
+        F: Fn(LogEntry) + Send + 'static,
+    {
+        // Start at the current end of each file, only new lines are streamed
+        let mut offsets: HashMap<PathBuf, (u64, LogSource)> = sources.into_iter()
+            .map(|source| {
+                let length = std::fs::metadata(&source.path).map(|m| m.len()).unwrap_or(0);
+                (PathBuf::from(&source.path), (length, source))
+            })
+            .collect();
+        // Watch the parent directories so rotated or recreated files keep streaming
+        let directories: HashSet<PathBuf> = offsets.keys()
+            .filter_map(|path| path.parent().map(|parent| parent.to_path_buf()))
+            .collect();
+
+        let mut watcher = notify::recommended_watcher(move |event: notify::Result<Event>| {
+            let Ok(event) = event else {
+                return;
+            };
+            if !matches!(event.kind, EventKind::Modify(_) | EventKind::Create(_)) {
+                return;
+            }
+            for path in &event.paths {
+                if let Some((offset, source)) = offsets.get_mut(path) {
+                    for line in Self::read_new_lines(path, offset) {
+                        let entry = LogEntry::parse(source, &line, &domains);
+                        if filter.matches(&entry) {
+                            on_entry(entry);
+                        }
+                    }
+                }
+            }
+        }).map_err(|e| e.to_string())?;
+
+        for directory in directories {
+            watcher.watch(&directory, RecursiveMode::NonRecursive)
+                .map_err(|e| format!("Unable to watch {}: {}", directory.display(), e))?;
+        }
+        *self.watcher.lock().unwrap() = Some(watcher);
+        Ok(())
+    }
+
+    pub fn unwatch(&self) {
+        self.watcher.lock().unwrap().take();
+    }
+
+    // Complete lines written since `offset`, which is moved past them
+    fn read_new_lines(path: &Path, offset: &mut u64) -> Vec<String> {
+        let Ok(mut file) = File::open(path) else {
+            return vec![];
+        };
+        let length = file.metadata().map(|m| m.len()).unwrap_or(0);
+        if length < *offset {
+            // Truncated or rotated
+            *offset = 0;
+        }
+        if file.seek(SeekFrom::Start(*offset)).is_err() {
+            return vec![];
+        }
+        let mut contents = Vec::new();
+        if file.read_to_end(&mut contents).is_err() {
+            return vec![];
+        }
+        let Some(end) = contents.iter().rposition(|&b| b == b'\n') else {
+            return vec![];
+        };
+        *offset += end as u64 + 1;
+        String::from_utf8_lossy(&contents[..end])
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| line.to_string())
+            .collect()
+    }
+}
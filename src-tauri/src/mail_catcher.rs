@@ -0,0 +1,309 @@
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+
+use chrono::Utc;
+use mail_parser::{Message, MessageParser, MimeHeaders};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::configuration::Configuration;
+use crate::constants::user;
+use crate::manager::file_system::ValetFilesystem;
+use crate::manager::interface::Filesystem;
+use crate::paths::{Paths, PathTrait};
+
+// Largest message accepted, advertised in the EHLO reply
+const MAX_MESSAGE_SIZE: usize = 52428800;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct MailCatcherSettings {
+    pub enabled: bool,
+    pub port: u16,
+}
+
+impl Default for MailCatcherSettings {
+    fn default() -> Self {
+        Self { enabled: false, port: 2525 }
+    }
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct MailSummary {
+    pub id: String,
+    pub from: String,
+    pub to: Vec<String>,
+    pub subject: String,
+    pub date: String,
+    pub size: usize,
+    pub has_html: bool,
+    pub attachments: usize,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct MailAttachment {
+    pub index: usize,
+    pub name: String,
+    pub content_type: String,
+    pub size: usize,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct MailMessage {
+    #[serde(flatten)]
+    pub summary: MailSummary,
+    pub text: Option<String>,
+    pub html: Option<String>,
+    pub attachments: Vec<MailAttachment>,
+}
+
+struct Listening {
+    stopped: Arc<AtomicBool>,
+    port: u16,
+    accept: JoinHandle<()>,
+}
+
+// In-process SMTP server that stores every message it receives under `Mail/`
+#[derive(Default)]
+pub struct MailCatcher {
+    running: Mutex<Option<Listening>>,
+}
+
+impl MailCatcher {
+    pub fn settings(config: &Configuration) -> MailCatcherSettings {
+        config.get("mail_catcher")
+            .and_then(|settings| serde_json::from_value(settings).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn set_settings<F>(&self, config: &Configuration, settings: MailCatcherSettings, on_message: F) -> Result<(), String>
+    where
+        F: Fn(MailSummary) + Send + Sync + 'static,
+    {
+        config.set("mail_catcher", json!(settings));
+        self.stop();
+        if settings.enabled {
+            self.start(settings.port, on_message)?;
+        }
+        Ok(())
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running.lock().unwrap().is_some()
+    }
+
+    // Listen on 127.0.0.1:port and call `on_message` for every stored message
+    pub fn start<F>(&self, port: u16, on_message: F) -> Result<(), String>
+    where
+        F: Fn(MailSummary) + Send + Sync + 'static,
+    {
+        let mut running = self.running.lock().unwrap();
+        if running.is_some() {
+            return Ok(());
+        }
+        ValetFilesystem.ensure_dir_exists(&Paths::mail_path(None), &user(), 0o755).map_err(|e| e.to_string())?;
+        let listener = TcpListener::bind(("127.0.0.1", port))
+            .map_err(|e| format!("Unable to listen for SMTP on port {}: {}", port, e))?;
+        let stopped = Arc::new(AtomicBool::new(false));
+        let on_message = Arc::new(on_message);
+        let flag = stopped.clone();
+        let accept = thread::spawn(move || {
+            for stream in listener.incoming() {
+                if flag.load(Ordering::SeqCst) {
+                    break;
+                }
+                let Ok(stream) = stream else {
+                    continue;
+                };
+                let on_message = on_message.clone();
+                thread::spawn(move || {
+                    if let Err(error) = Self::session(stream, on_message.as_ref()) {
+                        eprintln!("SMTP session failed: {}", error);
+                    }
+                });
+            }
+        });
+        *running = Some(Listening { stopped, port, accept });
+        println!("Catching mail on smtp://127.0.0.1:{}", port);
+        Ok(())
+    }
+
+    // Returns once the listener is closed, so the port can be bound again right away
+    pub fn stop(&self) {
+        let listening = self.running.lock().unwrap().take();
+        if let Some(listening) = listening {
+            listening.stopped.store(true, Ordering::SeqCst);
+            // Wake the accept loop so it can notice the flag
+            let _ = TcpStream::connect(("127.0.0.1", listening.port));
+            let _ = listening.accept.join();
+        }
+    }
+
+    pub fn list(query: Option<&str>) -> Vec<MailSummary> {
+        let query = query.map(|q| q.to_lowercase()).filter(|q| !q.is_empty());
+        let mut mails: Vec<MailSummary> = ValetFilesystem.scandir(&Paths::mail_path(None)).unwrap_or_default()
+            .into_iter()
+            .filter_map(|file| file.strip_suffix(".eml").map(|id| id.to_string()))
+            .filter_map(|id| {
+                let raw = fs::read(Paths::mail_path(Some(&format!("{}.eml", id)))).ok()?;
+                let message = MessageParser::default().parse(&raw)?;
+                if let Some(query) = &query {
+                    let haystack = format!(
+                        "{} {} {} {}",
+                        message.subject().unwrap_or_default(),
+                        Self::addresses(message.from()).join(" "),
+                        Self::addresses(message.to()).join(" "),
+                        message.body_text(0).unwrap_or_default(),
+                    ).to_lowercase();
+                    if !haystack.contains(query.as_str()) {
+                        return None;
+                    }
+                }
+                Some(Self::summary(&id, &message, raw.len()))
+            })
+            .collect();
+        mails.sort_by(|a, b| b.id.cmp(&a.id));
+        mails
+    }
+
+    pub fn read(id: &str) -> Result<MailMessage, String> {
+        let raw = Self::raw(id)?;
+        let message = MessageParser::default().parse(&raw).ok_or(format!("Unable to parse message [{}].", id))?;
+        let attachments = message.attachments()
+            .enumerate()
+            .map(|(index, part)| MailAttachment {
+                index,
+                name: part.attachment_name().unwrap_or("attachment").to_string(),
+                content_type: part.content_type()
+                    .map(|ct| match ct.subtype() {
+                        Some(subtype) => format!("{}/{}", ct.ctype(), subtype),
+                        None => ct.ctype().to_string(),
+                    })
+                    .unwrap_or("application/octet-stream".to_string()),
+                size: part.contents().len(),
+            })
+            .collect();
+        Ok(MailMessage {
+            summary: Self::summary(id, &message, raw.len()),
+            text: message.body_text(0).map(|text| text.to_string()),
+            html: message.body_html(0).filter(|_| message.html_body_count() > 0).map(|html| html.to_string()),
+            attachments,
+        })
+    }
+
+    pub fn save_attachment(id: &str, index: usize, path: &str) -> Result<(), String> {
+        let raw = Self::raw(id)?;
+        let message = MessageParser::default().parse(&raw).ok_or(format!("Unable to parse message [{}].", id))?;
+        let attachment = message.attachments().nth(index).ok_or(format!("Attachment {} not found.", index))?;
+        fs::write(path, attachment.contents()).map_err(|e| e.to_string())
+    }
+
+    pub fn delete(id: &str) -> Result<(), String> {
+        Self::validate_id(id)?;
+        ValetFilesystem.unlink(&Paths::mail_path(Some(&format!("{}.eml", id)))).map_err(|e| e.to_string())
+    }
+
+    pub fn clear() -> Result<(), String> {
+        for mail in Self::list(None) {
+            Self::delete(&mail.id)?;
+        }
+        Ok(())
+    }
+
+    fn raw(id: &str) -> Result<Vec<u8>, String> {
+        Self::validate_id(id)?;
+        fs::read(Paths::mail_path(Some(&format!("{}.eml", id)))).map_err(|_| format!("Message [{}] not found.", id))
+    }
+
+    fn validate_id(id: &str) -> Result<(), String> {
+        if id.is_empty() || !id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+            return Err(format!("Invalid message id [{}].", id));
+        }
+        Ok(())
+    }
+
+    fn summary(id: &str, message: &Message, size: usize) -> MailSummary {
+        MailSummary {
+            id: id.to_string(),
+            from: Self::addresses(message.from()).join(", "),
+            to: Self::addresses(message.to()),
+            subject: message.subject().unwrap_or_default().to_string(),
+            date: message.date().map(|date| date.to_rfc3339()).unwrap_or_default(),
+            size,
+            has_html: message.html_body_count() > 0,
+            attachments: message.attachment_count(),
+        }
+    }
+
+    fn addresses(address: Option<&mail_parser::Address>) -> Vec<String> {
+        address
+            .map(|address| address.iter()
+                .map(|addr| match (addr.name(), addr.address()) {
+                    (Some(name), Some(email)) => format!("{} <{}>", name, email),
+                    (None, Some(email)) => email.to_string(),
+                    (Some(name), None) => name.to_string(),
+                    (None, None) => String::new(),
+                })
+                .filter(|addr| !addr.is_empty())
+                .collect())
+            .unwrap_or_default()
+    }
+
+    // Speak just enough SMTP to accept messages from PHP mailers
+    fn session(stream: TcpStream, on_message: &(dyn Fn(MailSummary) + Send + Sync)) -> std::io::Result<()> {
+        static SEQUENCE: AtomicU64 = AtomicU64::new(0);
+        let mut writer = stream.try_clone()?;
+        let mut reader = BufReader::new(stream);
+        writer.write_all(b"220 valetui ESMTP ready\r\n")?;
+
+        let mut line = String::new();
+        loop {
+            line.clear();
+            if reader.read_line(&mut line)? == 0 {
+                return Ok(());
+            }
+            let command = line.trim_end().to_uppercase();
+            let verb = command.split_whitespace().next().unwrap_or_default();
+            match verb {
+                "EHLO" => writer.write_all(format!("250-valetui\r\n250-8BITMIME\r\n250 SIZE {}\r\n", MAX_MESSAGE_SIZE).as_bytes())?,
+                "HELO" | "MAIL" | "RCPT" | "RSET" | "NOOP" => writer.write_all(b"250 OK\r\n")?,
+                "DATA" => {
+                    writer.write_all(b"354 End data with <CR><LF>.<CR><LF>\r\n")?;
+                    let mut data: Vec<u8> = Vec::new();
+                    let mut chunk = Vec::new();
+                    loop {
+                        chunk.clear();
+                        if reader.read_until(b'\n', &mut chunk)? == 0 {
+                            return Ok(());
+                        }
+                        if chunk == b".\r\n" || chunk == b".\n" {
+                            break;
+                        }
+                        // Undo dot-stuffing
+                        let start = if chunk.starts_with(b"..") { 1 } else { 0 };
+                        data.extend_from_slice(&chunk[start..]);
+                        if data.len() > MAX_MESSAGE_SIZE {
+                            writer.write_all(b"552 Message size exceeds fixed maximum message size\r\n")?;
+                            return Ok(());
+                        }
+                    }
+                    let id = format!("{}-{}", Utc::now().format("%Y%m%d%H%M%S%f"), SEQUENCE.fetch_add(1, Ordering::SeqCst));
+                    fs::write(Paths::mail_path(Some(&format!("{}.eml", id))), &data)?;
+                    writer.write_all(format!("250 OK queued as {}\r\n", id).as_bytes())?;
+                    if let Some(message) = MessageParser::default().parse(&data) {
+                        on_message(Self::summary(&id, &message, data.len()));
+                    }
+                }
+                "QUIT" => {
+                    writer.write_all(b"221 Bye\r\n")?;
+                    return Ok(());
+                }
+                _ => writer.write_all(b"502 Command not implemented\r\n")?,
+            }
+        }
+    }
+}
@@ -1,111 +1,183 @@
-use crate::manager::apt::Apt;
+use serde_json::{json, Value};
+
+use crate::configuration::Configuration;
 use crate::manager::command::ValetCommandLine;
 use crate::manager::file_system::ValetFilesystem;
 use crate::manager::interface::{CommandLine, Filesystem, ServiceManager};
 use crate::manager::service_manager::ValetServiceManager;
+use crate::nginx::Nginx;
+use crate::paths::{Paths, PathTrait};
+use crate::php_fpm::PhpFpm;
+use crate::site_secure::SiteSecure;
+use crate::stub::Stub;
 
-struct Mailpit {
-    pm: Apt,
+pub struct Mailpit {
     sm: ValetServiceManager,
     cli: ValetCommandLine,
     files: ValetFilesystem,
+    config: Configuration,
+    site_secure: SiteSecure,
+    nginx: Nginx,
+    fpm: PhpFpm,
 }
 
 impl Mailpit {
     const SERVICE_NAME: &'static str = "mailpit";
+    const BINARY: &'static str = "/usr/local/bin/mailpit";
+    const SMTP_ADDRESS: &'static str = "127.0.0.1:1025";
+    const HTTP_ADDRESS: &'static str = "http://127.0.0.1:8025";
+
     pub fn new(
-        pm: Apt, sm: ValetServiceManager, cli: ValetCommandLine, files: ValetFilesystem) -> Self {
+        sm: ValetServiceManager,
+        cli: ValetCommandLine,
+        files: ValetFilesystem,
+        config: Configuration,
+        site_secure: SiteSecure,
+        nginx: Nginx,
+        fpm: PhpFpm,
+    ) -> Self {
         Self {
-            pm,
             sm,
             cli,
             files,
+            config,
+            site_secure,
+            nginx,
+            fpm,
         }
     }
     // Install method
-    fn install(&self) {
-        self.ensure_installed();
-        self.create_service();
+    pub fn install(&self) -> Result<(), String> {
+        self.ensure_installed()?;
+        self.create_service()?;
         self.sm.start(vec![Self::SERVICE_NAME]);
+        self.configure_sendmail(true);
 
         // Handle mailhog removal and unsecuring if necessary
         if !self.sm.disabled("mailhog") {
             self.sm.disable("mailhog");
             if self.files.exists("/opt/valet-linux/mailhog") {
-                self.files.remove(&["/opt/valet-linux/mailhog"]).unwrap();
+                self.files.remove(&["/opt/valet-linux/mailhog"]).map_err(|e| e.to_string())?;
             }
-            let domain = "example.com"; // Replace with actual domain fetch
-            if self.files.exists(&format!("/path/to/Nginx/mailhog.{}", domain)) {
-                // SiteSecureFacade::unsecure("mailhog.$domain");
-                println!("Unsecuring mailhog.{}", domain);
+            let mailhog_url = format!("mailhog.{}", self.config.domain());
+            if self.files.exists(&Paths::nginx_path(Some(&mailhog_url))) {
+                self.remove_site(&mailhog_url)?;
             }
         }
+        Ok(())
     }
 
     // Start method
-    fn start(&self) {
+    pub fn start(&self) {
         self.sm.start(vec![Self::SERVICE_NAME]);
     }
 
-    fn restart(&self) {
+    pub fn restart(&self) {
         self.sm.restart(vec![Self::SERVICE_NAME]);
     }
 
     // Stop method
-    fn stop(&self) {
+    pub fn stop(&self) {
         self.sm.stop(vec![Self::SERVICE_NAME]);
     }
 
     // Status method
-    fn status(&self) {
+    pub fn status(&self) {
         self.sm.print_status(Self::SERVICE_NAME);
     }
 
+    pub fn is_running(&self) -> bool {
+        self.sm.is_running(Self::SERVICE_NAME)
+    }
+
     // Uninstall method
-    fn uninstall(&self) {
+    pub fn uninstall(&self) -> Result<(), String> {
         self.stop();
+        self.sm.disable(Self::SERVICE_NAME);
+        self.files.unlink(self.service_path()).map_err(|e| e.to_string())?;
+        self.remove_site(&self.url())?;
+        self.configure_sendmail(false);
+        self.nginx.restart();
+        Ok(())
+    }
+
+    pub fn url(&self) -> String {
+        format!("mails.{}", self.config.domain())
+    }
+
+    pub fn secured(&self) -> bool {
+        self.config.get("mailpit_secure").and_then(|v| v.as_bool()).unwrap_or(false)
+    }
+
+    // Serve the web UI over https at mails.{domain}, or back over http
+    pub fn set_secure(&self, secure: bool) -> Result<(), String> {
+        self.config.set("mailpit_secure", json!(secure));
+        self.update_domain()
+    }
+
+    // Drop the proxy site of a previous domain, e.g. when the TLD changes
+    pub fn remove_site(&self, url: &str) -> Result<(), String> {
+        self.site_secure.unsecure(url, false);
+        self.files.unlink(&Paths::nginx_path(Some(url))).map_err(|e| e.to_string())
     }
 
     // Ensure Mailpit is installed method
-    fn ensure_installed(&self) {
+    fn ensure_installed(&self) -> Result<(), String> {
         if !self.is_available() {
-            self.cli.run_as_user("curl -sL https://raw.githubusercontent.com/axllent/mailpit/develop/install.sh | bash").unwrap();
+            self.cli.run_as_user("curl -sL https://raw.githubusercontent.com/axllent/mailpit/develop/install.sh | bash")
+                .map_err(|e| format!("Unable to install Mailpit: {}", e))?;
         }
+        Ok(())
     }
 
     // Create Mailpit service method
-    fn create_service(&self) {
-        let service_path;
-        let service_file;
-        if self.sm.is_systemd() {
-            service_path = "/etc/systemd/system/mailpit.service";
-            service_file = "/path/to/cli/stubs/init/mailpit";
-        } else {
-            service_path = "/etc/init.d/mailpit";
-            service_file = "/path/to/cli/stubs/init/mailpit.sh";
-        }
-
-        let service_content = self.files.get(service_file).unwrap();
-        self.files.put(service_path, &service_content).unwrap();
+    fn create_service(&self) -> Result<(), String> {
+        let service_file = if self.sm.is_systemd() { "init/mailpit" } else { "init/mailpit.sh" };
+        let service_content = Stub::load(service_file)?.render()?;
+        self.files.put(self.service_path(), &service_content).map_err(|e| e.to_string())?;
 
         if !self.sm.is_systemd() {
-            // self.cli.run("chmod +x $servicePath");
-            println!("chmod +x {}", service_path);
+            self.files.chmod(self.service_path(), 0o755).map_err(|e| e.to_string())?;
         }
 
         self.sm.enable(Self::SERVICE_NAME);
 
-        self.update_domain();
+        self.update_domain()
     }
 
     // Update domain method
-    fn update_domain(&self) {
-        let domain = "example.com"; // Replace with actual domain fetch
-        println!("Updating domain for HTTP access: mails.{}", domain);
-        todo!()
+    pub fn update_domain(&self) -> Result<(), String> {
+        let url = self.url();
+        self.site_secure.proxy(&url, Self::HTTP_ADDRESS, self.secured())?;
+        self.nginx.restart();
+        Ok(())
+    }
+
+    // Point PHP's mail() of every valet pool at mailpit's sendmail
+    fn configure_sendmail(&self, enabled: bool) {
+        let sendmail = if enabled {
+            json!(format!("{} sendmail -t --smtp-addr {}", Self::BINARY, Self::SMTP_ADDRESS))
+        } else {
+            Value::Null
+        };
+        self.config.set("sendmail_path", sendmail);
+        if let Err(error) = self.fpm.reinstall_configurations() {
+            eprintln!("Unable to update PHP-FPM sendmail_path: {}", error);
+        }
+    }
+
+    fn service_path(&self) -> &'static str {
+        if self.sm.is_systemd() {
+            "/etc/systemd/system/mailpit.service"
+        } else {
+            "/etc/init.d/mailpit"
+        }
     }
+
     fn is_available(&self) -> bool {
-        let output = self.cli.run_as_user("which mailpit").unwrap();
-        !output.trim().is_empty()
+        match self.cli.run_as_user("which mailpit") {
+            Ok(output) => !output.trim().is_empty(),
+            Err(_) => false,
+        }
     }
-}
\ No newline at end of file
+}
@@ -2,21 +2,39 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 mod manager;
+mod access_log;
 mod constants;
 mod configuration;
 mod requirements;
+mod site_info;
 mod site_secure;
+mod site_watcher;
 mod paths;
 mod site;
 mod nginx;
+mod nginx_config;
+mod stub;
 mod devtools;
 mod php_fpm;
 mod dnsmasq;
+mod domain;
 mod mailpit;
+mod mail_catcher;
+mod lan_share;
+mod tunnels;
+mod services;
+mod commands;
+mod fastcgi;
+mod fpm_status;
+mod supervisor;
+mod logs;
 
-use tauri::{Manager, SystemTray, SystemTrayEvent};
-use tauri::{CustomMenuItem, SystemTrayMenu};
+use tauri::{Manager, RunEvent, SystemTray, SystemTrayEvent};
+use tauri::{CustomMenuItem, SystemTrayMenu, SystemTraySubmenu};
 use crate::configuration::Configuration;
+use crate::lan_share::LanShare;
+use crate::logs::LogWatcher;
+use crate::mail_catcher::MailCatcher;
 use crate::manager::apt::Apt;
 use crate::manager::command::ValetCommandLine;
 use crate::manager::file_system::ValetFilesystem;
@@ -25,6 +43,9 @@ use crate::manager::systemd::ValetSystemDManager;
 use crate::nginx::Nginx;
 use crate::requirements::Requirements;
 use crate::site_secure::SiteSecure;
+use crate::site_watcher::SiteWatcher;
+use crate::supervisor::Supervisor;
+use crate::tunnels::Tunnels;
 
 // Learn more about Tauri commands at https://tauri.app/v1/guides/features/command
 #[tauri::command]
@@ -50,13 +71,36 @@ fn main() {
     let hide = CustomMenuItem::new("hide".to_string(), "Hide");
     let quit = CustomMenuItem::new("quit".to_string(), "Quit");
     let open = CustomMenuItem::new("open".to_string(), "Open");
+    let services_menu = commands::registry().all().iter().fold(SystemTrayMenu::new(), |menu, service| {
+        menu.add_item(CustomMenuItem::new(format!("restart:{}", service.name()), format!("Restart {}", service.label())))
+    });
     let tray_menu = SystemTrayMenu::new()
         .add_item(open)
+        .add_submenu(SystemTraySubmenu::new("Services", services_menu))
         .add_item(hide)
         .add_item(quit);
     let tray = SystemTray::new().with_menu(tray_menu);
     tauri::Builder::default()
         .system_tray(tray)
+        .manage(MailCatcher::default())
+        .manage(LogWatcher::default())
+        .manage(Supervisor::default())
+        .manage(SiteWatcher::default())
+        .manage(LanShare::default())
+        .manage(Tunnels::default())
+        .setup(|app| {
+            let settings = MailCatcher::settings(&Configuration::new(ValetFilesystem));
+            if settings.enabled {
+                let catcher = app.state::<MailCatcher>();
+                if let Err(error) = catcher.start(settings.port, commands::mail::notify(app.handle())) {
+                    eprintln!("{}", error);
+                }
+            }
+            if let Err(error) = commands::sites::watch_sites(&app.handle()) {
+                eprintln!("{}", error);
+            }
+            Ok(())
+        })
         .on_system_tray_event(|app, event| match event {
             SystemTrayEvent::MenuItemClick { id, .. } => {
                 let _item_handle = app.tray_handle().get_item(&id);
@@ -74,12 +118,128 @@ fn main() {
                         let window = app.get_window("main").unwrap();
                         window.close().unwrap();
                     }
+                    id if id.starts_with("restart:") => {
+                        if let Err(error) = commands::registry().control(&id["restart:".len()..], "restart") {
+                            eprintln!("{}", error);
+                        }
+                    }
                     _ => {}
                 }
             }
             _ => {}
         })
-        .invoke_handler(tauri::generate_handler![greet])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .invoke_handler(tauri::generate_handler![
+            greet,
+            commands::php::php_ini,
+            commands::php::set_php_ini,
+            commands::php::fpm_pool,
+            commands::php::set_fpm_pool,
+            commands::php::enable_fpm_monitoring,
+            commands::php::fpm_status,
+            commands::php::fpm_slow_log,
+            commands::php::switch_php_version,
+            commands::php::install_php_shims,
+            commands::php::uninstall_php_shims,
+            commands::database::database_install,
+            commands::database::database_start,
+            commands::database::database_stop,
+            commands::database::database_restart,
+            commands::database::databases,
+            commands::database::database_create,
+            commands::database::database_drop,
+            commands::database::database_import,
+            commands::database::database_export,
+            commands::database::postgres_install,
+            commands::database::postgres_start,
+            commands::database::postgres_stop,
+            commands::database::postgres_restart,
+            commands::database::postgres_databases,
+            commands::database::postgres_create,
+            commands::database::postgres_drop,
+            commands::database::set_postgres_site_databases,
+            commands::cache::redis_install,
+            commands::cache::redis_start,
+            commands::cache::redis_stop,
+            commands::cache::redis_restart,
+            commands::cache::redis_uninstall,
+            commands::cache::redis_settings,
+            commands::cache::set_redis_settings,
+            commands::cache::redis_flush,
+            commands::cache::redis_stats,
+            commands::cache::memcached_install,
+            commands::cache::memcached_start,
+            commands::cache::memcached_stop,
+            commands::cache::memcached_restart,
+            commands::cache::memcached_uninstall,
+            commands::cache::memcached_settings,
+            commands::cache::set_memcached_settings,
+            commands::cache::memcached_flush,
+            commands::cache::memcached_stats,
+            commands::domains::domains,
+            commands::domains::set_domains,
+            commands::domains::check_domain,
+            commands::domains::set_domain,
+            commands::services::services,
+            commands::services::service_action,
+            commands::services::mailpit_secure,
+            commands::mail::mail_catcher_settings,
+            commands::mail::set_mail_catcher_settings,
+            commands::mail::mail_catcher_running,
+            commands::mail::mails,
+            commands::mail::mail,
+            commands::mail::mail_delete,
+            commands::mail::mail_clear,
+            commands::mail::mail_save_attachment,
+            commands::logs::log_sources,
+            commands::logs::log_tail,
+            commands::logs::log_watch,
+            commands::logs::log_unwatch,
+            commands::sites::site_access_log,
+            commands::sites::set_site_access_log,
+            commands::sites::site_requests,
+            commands::sites::site_request_stats,
+            commands::sites::site_proxy,
+            commands::sites::site_unproxy,
+            commands::sites::site_proxies,
+            commands::sites::sites,
+            commands::sites::site_info,
+            commands::sites::site_aliases,
+            commands::sites::set_site_aliases,
+            commands::sites::site_share,
+            commands::sites::site_unshare,
+            commands::sites::site_shares,
+            commands::tunnels::tunnel_providers,
+            commands::tunnels::tunnel_settings,
+            commands::tunnels::set_tunnel_settings,
+            commands::tunnels::site_tunnel_open,
+            commands::tunnels::site_tunnel_close,
+            commands::tunnels::site_tunnels,
+            commands::sites::site_snippets,
+            commands::sites::save_site_snippet,
+            commands::sites::delete_site_snippet,
+            commands::sites::parked_paths,
+            commands::sites::park,
+            commands::sites::unpark,
+            commands::sites::forget,
+            commands::sites::link,
+            commands::sites::unlink,
+            commands::sites::links,
+            commands::processes::process_definitions,
+            commands::processes::processes,
+            commands::processes::process_start,
+            commands::processes::process_stop,
+            commands::processes::process_restart,
+        ])
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app, event| {
+            // Don't leave queue workers and dev servers running behind us
+            if let RunEvent::Exit = event {
+                app.state::<Supervisor>().stop_all();
+                let listeners = app.state::<Tunnels>().close_all();
+                if app.state::<LanShare>().stop_all() || listeners {
+                    commands::nginx().restart();
+                }
+            }
+        });
 }
@@ -14,6 +14,8 @@ impl Apt {
         ("redis", "redis-server"),
         ("mysql", "mysql-server"),
         ("mariadb", "mariadb-server"),
+        ("postgresql", "postgresql"),
+        ("memcached", "memcached"),
     ];
     pub fn new(cli: Box<dyn CommandLine>, service_manager: Box<dyn ServiceManager>) -> Self {
         Self { cli, service_manager, php_fpm_pattern_by_version: HashMap::new() }
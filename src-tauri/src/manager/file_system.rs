@@ -22,7 +22,7 @@ impl Filesystem for ValetFilesystem {
             }
 
             if Path::new(file).is_dir() && !fs::symlink_metadata(file)?.file_type().is_symlink() {
-                self.remove(&[file])?;
+                fs::remove_dir_all(file)?;
             } else {
                 fs::remove_file(file)?;
             }
@@ -131,7 +131,7 @@ impl Filesystem for ValetFilesystem {
     }
 
     fn symlink(&self, target: &str, link: &str) -> Result<(), Error> {
-        if self.exists(link) {
+        if self.exists(link) || self.is_link(link) {
             self.unlink(link)?;
         }
 
@@ -144,7 +144,8 @@ impl Filesystem for ValetFilesystem {
     }
 
     fn unlink(&self, path: &str) -> Result<(), Error> {
-        if self.exists(path) {
+        // A broken symlink does not "exist" but must still be removable
+        if self.exists(path) || self.is_link(path) {
             fs::remove_file(path)?;
         }
 
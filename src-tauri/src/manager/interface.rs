@@ -20,6 +20,7 @@ pub trait ServiceManager {
     fn stop(&self, services: Vec<&str>);
     fn restart(&self, services: Vec<&str>);
     fn print_status(&self, service: &str);
+    fn is_running(&self, service: &str) -> bool;
     fn disabled(&self, service: &str) -> bool;
     fn disable(&self, service: &str);
     fn enable(&self, service: &str);
@@ -50,19 +50,18 @@ impl ServiceManager for ValetServiceManager {
 
     fn print_status(&self, service: &str) {
         let real_service = self.get_real_service(service).expect("Unable to determine service name.");
-        let status = self.cli.run(&format!("service {} status", real_service));
-        match status {
-            Ok(output) => {
-                if output.contains("running") {
-                    println!("{} is running...", service);
-                } else {
-                    println!("{} is stopped...", service);
-                }
-            }
-            Err(e) => eprintln!("Error: {}", e),
+        if self.is_running(&real_service) {
+            println!("{} is running...", service);
+        } else {
+            println!("{} is stopped...", service);
         }
     }
 
+    // `service … status` exits 0 only while the service runs, "is not running" also contains "running"
+    fn is_running(&self, service: &str) -> bool {
+        self.cli.run(&format!("service {} status", service)).is_ok()
+    }
+
     fn disabled(&self, service: &str) -> bool {
         let real_service = self.get_real_service(service).expect(format!("Unable to determine service with name {}.", service).as_str());
         match self.cli.run(&format!("systemctl is-enabled {}", real_service)) {
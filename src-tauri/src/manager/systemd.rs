@@ -63,6 +63,13 @@ impl ServiceManager for ValetSystemDManager {
         }
     }
 
+    fn is_running(&self, service: &str) -> bool {
+        match self.cli.run(&format!("systemctl is-active {}", service)) {
+            Ok(output) => output.trim() == "active",
+            Err(_) => false,
+        }
+    }
+
     fn disabled(&self, service: &str) -> bool {
         let real_service = self.get_real_service(service).expect("Unable to determine service name.");
         match self.cli.run(&format!("systemctl is-enabled {}", real_service)) {
@@ -6,6 +6,7 @@ use crate::manager::file_system::ValetFilesystem;
 use crate::manager::interface::{CommandLine, Filesystem, PackageManager, ServiceManager};
 use crate::manager::service_manager::ValetServiceManager;
 use crate::site_secure::SiteSecure;
+use crate::stub::Stub;
 
 pub struct Nginx {
     pm: Apt,
@@ -47,6 +48,17 @@ impl Nginx {
         self.install_nginx_directory();
     }
 
+    pub fn uninstall(&self) {
+        self.stop();
+        self.files.restore(NGINX_CONF).unwrap();
+        self.files.unlink(SITES_ENABLED_CONF).unwrap();
+        self.files.unlink(SITES_AVAILABLE_CONF).unwrap();
+    }
+
+    pub fn start(&self) {
+        self.sm.start(vec!["nginx"])
+    }
+
     pub fn restart(&self) {
         self.sm.restart(vec!["nginx"])
     }
@@ -57,6 +69,15 @@ impl Nginx {
     pub fn status(&self) {
         self.sm.print_status("nginx");
     }
+    pub fn is_running(&self) -> bool {
+        self.sm.is_running("nginx")
+    }
+    pub fn ports(&self) -> Vec<u16> {
+        ["port", "https_port"].iter()
+            .filter_map(|key| self.configuration.get(key))
+            .filter_map(|value| value.as_str().and_then(|v| v.parse().ok()).or(value.as_u64().map(|v| v as u16)))
+            .collect()
+    }
     fn handle_apache_service(&self) {
         if self.pm.installed("apache2") {
             return;
@@ -70,32 +91,39 @@ impl Nginx {
         let entries = self.files.scandir(&format!("{}/Nginx", Valet::home_path())).unwrap();
         let filtered_entries: Vec<String> = entries
             .into_iter()
-            .filter(|file| !file.starts_with('.'))
+            .filter(|file| !file.starts_with('.') && !self.files.is_dir(&format!("{}/Nginx/{}", Valet::home_path(), file)))
             .collect();
         filtered_entries
     }
 
     fn install_configuration(&self) {
-        let contents = self.files.get(format!("{}/stubs/nginx.conf", Valet::root_path()).as_str()).unwrap();
         let mut pid_path = "pid /run/nginx.pid";
         let has_pid_option = self.cli.run("cat /lib/systemd/system/nginx.service").unwrap().contains("pid /");
         if has_pid_option {
             pid_path = "# pid /run/nginx.pid";
         }
+        let contents = Stub::load("nginx.conf").unwrap()
+            .set("USER", &user())
+            .set("GROUP", &group().unwrap())
+            .set("HOME_PATH", &Valet::home_path())
+            .set("PID", pid_path)
+            .render()
+            .unwrap();
         self.files.backup(NGINX_CONF).unwrap();
-        let content_cloned = contents.as_str();
-        let binding = content_cloned
-            .replace("VALET_USER", user().as_str())
-            .replace("VALET_GROUP", &group().unwrap())
-            .replace("VALET_HOME_PATH", Valet::home_path().as_str())
-            .replace("VALET_PID", pid_path);
-        let replaced_contents = binding.as_str();
         self.files.put(
             NGINX_CONF,
-            replaced_contents,
+            &contents,
         ).unwrap()
     }
 
+    // Installs from before the valet_json access log format need a fresh nginx.conf
+    pub fn ensure_access_log_format(&self) {
+        let installed = self.files.get(NGINX_CONF).unwrap_or_default();
+        if !installed.contains("log_format valet_json") {
+            self.install_configuration();
+        }
+    }
+
     pub fn install_nginx_directory(&self) {
         let nginx_dir = format!("{}/Nginx", Valet::home_path());
         if !self.files.is_dir(nginx_dir.as_str()) {
@@ -105,19 +133,26 @@ impl Nginx {
         self.rewrite_secure_nginx_files()
     }
 
+    // Regenerate server names and certificates of existing sites for the configured TLDs
     fn rewrite_secure_nginx_files(&self) {
-        let domain = self.configuration.get("domain").unwrap().to_string();
-        self.site_secure.re_secure_for_new_domain(domain.clone().as_str(), domain.clone().as_str());
+        self.site_secure.update_domains(&self.configuration.domain()).unwrap();
+    }
+
+    // `nginx -t`, so a broken server block is reported instead of taking nginx down
+    pub fn test_configuration(&self) -> Result<(), String> {
+        self.cli.run("sudo nginx -t").map(|_| ()).map_err(|e| e.to_string())
     }
 
     pub fn install_server(&self, socket_file_name: Option<&str>) {
-        let default_valet_conf = self.files.get(format!("{}/stubs/valet.conf", Valet::home_path()).as_str()).unwrap();
-        let valet_conf = default_valet_conf
-            .replace("VALET_HOME_PATH", Valet::home_path().as_str())
-            .replace("VALET_FPM_SOCKET_FILE", format!("{}/{}", Valet::home_path(), socket_file_name.unwrap_or("")).as_str())
-            .replace("VALET_SERVER_PATH", VALET_SERVER_PATH)
-            .replace("VALET_STATIC_PREFIX", VALET_STATIC_PREFIX)
-            .replace("VALET_PORT", self.configuration.get("port").unwrap().to_string().as_str());
+        let port = self.configuration.get("port").and_then(|port| port.as_str().map(|port| port.to_string()));
+        let valet_conf = Stub::load("valet.conf").unwrap()
+            .set("HOME_PATH", &Valet::home_path())
+            .set("FPM_SOCKET_FILE", &format!("{}/{}", Valet::home_path(), socket_file_name.unwrap_or("")))
+            .set("SERVER_PATH", VALET_SERVER_PATH)
+            .set("STATIC_PREFIX", VALET_STATIC_PREFIX)
+            .set("PORT", port.as_deref().unwrap_or("80"))
+            .render()
+            .unwrap();
 
         self.files.put(SITES_AVAILABLE_CONF, valet_conf.as_str()).unwrap();
         if self.files.exists("/etc/nginx/sites-enabled/default") {
@@ -125,6 +160,6 @@ impl Nginx {
         }
         self.cli.run(format!("ln -snf {}{}", SITES_AVAILABLE_CONF, SITES_ENABLED_CONF).as_str()).unwrap();
         self.files.backup("/etc/nginx/fastcgi_params").unwrap();
-        self.files.put("/etc/nginx/fastcgi_params", self.files.get(format!("{}/stubs/fastcgi_params", Valet::root_path()).as_str()).unwrap().as_str()).unwrap();
+        self.files.put("/etc/nginx/fastcgi_params", &Stub::load("fastcgi_params").unwrap().render().unwrap()).unwrap();
     }
 }
\ No newline at end of file
@@ -0,0 +1,272 @@
+use regex::Regex;
+
+// A directive argument with its raw span (quotes included) in the source
+#[derive(Debug, Clone)]
+pub struct Argument {
+    pub value: String,
+    pub quoted: bool,
+    pub start: usize,
+    pub end: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct Directive {
+    pub name: String,
+    pub args: Vec<Argument>,
+    pub children: Vec<Directive>,
+}
+
+// Parsed nginx configuration that keeps enough positions to rewrite the source in place
+#[derive(Debug, Clone)]
+pub struct NginxConfig {
+    pub directives: Vec<Directive>,
+    pub comments: Vec<String>,
+}
+
+#[derive(Debug)]
+enum Token {
+    Word(Argument),
+    Semicolon,
+    Open,
+    Close,
+}
+
+impl NginxConfig {
+    pub fn parse(source: &str) -> Result<Self, String> {
+        let (tokens, comments) = Self::tokenize(source)?;
+        let mut position = 0;
+        let directives = Self::parse_block(&tokens, &mut position, false)?;
+        Ok(NginxConfig { directives, comments })
+    }
+
+    // All directives with the given name, at any depth
+    pub fn find(&self, name: &str) -> Vec<&Directive> {
+        fn walk<'a>(directives: &'a [Directive], name: &str, found: &mut Vec<&'a Directive>) {
+            for directive in directives {
+                if directive.name == name {
+                    found.push(directive);
+                }
+                walk(&directive.children, name, found);
+            }
+        }
+        let mut found = Vec::new();
+        walk(&self.directives, name, &mut found);
+        found
+    }
+
+    pub fn is_isolated(&self) -> bool {
+        self.comments.iter().any(|comment| comment.starts_with("ISOLATED_PHP_VERSION="))
+    }
+
+    // Point every valet `fastcgi_pass unix:` socket at `socket`.
+    // Returns None when the site is isolated or nothing had to change.
+    pub fn rewrite_fpm_socket(source: &str, socket: &str) -> Result<Option<String>, String> {
+        let config = Self::parse(source)?;
+        if config.is_isolated() {
+            return Ok(None);
+        }
+        let valet_socket = Regex::new(r"(^|/)valet\d*\.sock$").unwrap();
+        let target = format!("unix:{}", socket);
+        let mut replacements: Vec<(usize, usize, String)> = config.find("fastcgi_pass")
+            .into_iter()
+            .filter_map(|directive| directive.args.first())
+            .filter(|arg| arg.value.starts_with("unix:") && valet_socket.is_match(&arg.value))
+            .filter(|arg| arg.value != target)
+            .map(|arg| {
+                let raw = if arg.quoted { format!("\"{}\"", target) } else { target.clone() };
+                (arg.start, arg.end, raw)
+            })
+            .collect();
+        if replacements.is_empty() {
+            return Ok(None);
+        }
+
+        replacements.sort_by_key(|replacement| std::cmp::Reverse(replacement.0));
+        let mut updated = source.to_string();
+        for (start, end, raw) in replacements {
+            updated.replace_range(start..end, &raw);
+        }
+        Ok(Some(updated))
+    }
+
+    fn tokenize(source: &str) -> Result<(Vec<Token>, Vec<String>), String> {
+        let bytes = source.as_bytes();
+        let mut tokens = Vec::new();
+        let mut comments = Vec::new();
+        let mut i = 0;
+        while i < bytes.len() {
+            match bytes[i] {
+                b' ' | b'\t' | b'\r' | b'\n' => i += 1,
+                b'#' => {
+                    let end = source[i..].find('\n').map_or(bytes.len(), |n| i + n);
+                    comments.push(source[i + 1..end].trim().to_string());
+                    i = end;
+                }
+                b';' => {
+                    tokens.push(Token::Semicolon);
+                    i += 1;
+                }
+                b'{' => {
+                    tokens.push(Token::Open);
+                    i += 1;
+                }
+                b'}' => {
+                    tokens.push(Token::Close);
+                    i += 1;
+                }
+                quote @ (b'"' | b'\'') => {
+                    let start = i;
+                    let mut value = String::new();
+                    i += 1;
+                    loop {
+                        match bytes.get(i) {
+                            None => return Err(format!("Unterminated string starting at byte {}.", start)),
+                            Some(b'\\') if i + 1 < bytes.len() => {
+                                value.push(bytes[i + 1] as char);
+                                i += 2;
+                            }
+                            Some(&c) if c == quote => {
+                                i += 1;
+                                break;
+                            }
+                            Some(_) => {
+                                let ch = source[i..].chars().next().unwrap();
+                                value.push(ch);
+                                i += ch.len_utf8();
+                            }
+                        }
+                    }
+                    tokens.push(Token::Word(Argument { value, quoted: true, start, end: i }));
+                }
+                _ => {
+                    let start = i;
+                    while i < bytes.len() && !matches!(bytes[i], b' ' | b'\t' | b'\r' | b'\n' | b';' | b'{' | b'}') {
+                        i += 1;
+                    }
+                    tokens.push(Token::Word(Argument {
+                        value: source[start..i].to_string(),
+                        quoted: false,
+                        start,
+                        end: i,
+                    }));
+                }
+            }
+        }
+        Ok((tokens, comments))
+    }
+
+    fn parse_block(tokens: &[Token], position: &mut usize, nested: bool) -> Result<Vec<Directive>, String> {
+        let mut directives = Vec::new();
+        while *position < tokens.len() {
+            let name = match &tokens[*position] {
+                Token::Close if nested => {
+                    *position += 1;
+                    return Ok(directives);
+                }
+                Token::Word(word) => word.value.clone(),
+                token => return Err(format!("Unexpected {:?} in nginx configuration.", token)),
+            };
+            *position += 1;
+
+            let mut args = Vec::new();
+            let mut children = Vec::new();
+            loop {
+                match tokens.get(*position) {
+                    Some(Token::Word(arg)) => {
+                        args.push(arg.clone());
+                        *position += 1;
+                    }
+                    Some(Token::Semicolon) => {
+                        *position += 1;
+                        break;
+                    }
+                    Some(Token::Open) => {
+                        *position += 1;
+                        children = Self::parse_block(tokens, position, true)?;
+                        break;
+                    }
+                    _ => return Err(format!("Directive [{}] is not terminated.", name)),
+                }
+            }
+            directives.push(Directive { name, args, children });
+        }
+        if nested {
+            return Err("Unexpected end of nginx configuration, missing \"}\".".to_string());
+        }
+        Ok(directives)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::NginxConfig;
+    use crate::stub::Stub;
+
+    const SOCKET: &str = "/home/valet/.config/valetui/valet83.sock";
+
+    #[test]
+    fn rewrites_socket_of_secured_site() {
+        let source = include_str!("../tests/fixtures/nginx/secure.app.test");
+        let updated = NginxConfig::rewrite_fpm_socket(source, SOCKET).unwrap().unwrap();
+        assert!(updated.contains(&format!("fastcgi_pass unix:{};", SOCKET)));
+        assert!(!updated.contains("valet82.sock"));
+        assert_eq!(updated.replace(&format!("unix:{}", SOCKET), ""), source.replace("unix:/home/valet/.config/valetui/valet82.sock", ""));
+    }
+
+    #[test]
+    fn keeps_quotes_around_socket() {
+        let source = include_str!("../tests/fixtures/nginx/quoted.app.test");
+        let updated = NginxConfig::rewrite_fpm_socket(source, SOCKET).unwrap().unwrap();
+        assert!(updated.contains(&format!("fastcgi_pass \"unix:{}\";", SOCKET)));
+    }
+
+    #[test]
+    fn skips_isolated_site() {
+        let source = include_str!("../tests/fixtures/nginx/isolated.app.test");
+        assert!(NginxConfig::rewrite_fpm_socket(source, SOCKET).unwrap().is_none());
+    }
+
+    #[test]
+    fn skips_proxy_site() {
+        let source = include_str!("../tests/fixtures/nginx/proxy.app.test");
+        assert!(NginxConfig::rewrite_fpm_socket(source, SOCKET).unwrap().is_none());
+    }
+
+    #[test]
+    fn skips_site_already_on_socket() {
+        let source = include_str!("../tests/fixtures/nginx/secure.app.test")
+            .replace("/home/valet/.config/valetui/valet82.sock", SOCKET);
+        assert!(NginxConfig::rewrite_fpm_socket(&source, SOCKET).unwrap().is_none());
+    }
+
+    // Server blocks as valet writes them from the bundled stub, isolated or following the default PHP
+    fn rendered_site(isolated: bool) -> String {
+        let stub = Stub::from_source("site.valet.conf", Stub::bundled("site.valet.conf").unwrap())
+            .set("HOME_PATH", "/home/valet/.config/valetui")
+            .set("SERVER_PATH", "/path/to/valet/server")
+            .set("STATIC_PREFIX", "/static")
+            .set("ALIASES", "")
+            .set("SITE", "app.test")
+            .set("HTTP_PORT", "80")
+            .set("FPM_SOCKET_FILE", "/home/valet/.config/valetui/valet82.sock")
+            .set("PHP_VALUE", "")
+            .set("ISOLATED_PHP_VERSION", "8.2")
+            .flag("isolated", isolated)
+            .flag("php_ini", false)
+            .flag("access_log", false);
+        stub.render().unwrap()
+    }
+
+    #[test]
+    fn switches_rendered_sites_but_not_isolated_ones() {
+        let updated = NginxConfig::rewrite_fpm_socket(&rendered_site(false), SOCKET).unwrap().unwrap();
+        assert!(updated.contains(&format!("fastcgi_pass \"unix:{}\";", SOCKET)));
+        assert!(NginxConfig::rewrite_fpm_socket(&rendered_site(true), SOCKET).unwrap().is_none());
+    }
+
+    #[test]
+    fn rejects_unbalanced_blocks() {
+        assert!(NginxConfig::parse("server { listen 80;").is_err());
+        assert!(NginxConfig::parse("server { listen 80 }").is_err());
+    }
+}
@@ -7,6 +7,9 @@ pub trait PathTrait {
     fn certificates_path(file: Option<&str>) -> String;
     fn ca_path(file: Option<&str>) -> String;
     fn nginx_path(file: Option<&str>) -> String;
+    fn bin_path(file: Option<&str>) -> String;
+    fn mail_path(file: Option<&str>) -> String;
+    fn snippets_path(file: Option<&str>) -> String;
 }
 
 impl PathTrait for  Paths {
@@ -27,4 +30,16 @@ impl PathTrait for  Paths {
         let file_path = file.map_or("".to_string(), |f| format!("/{}", f));
         format!("{}/Nginx{}", Valet::home_path(), file_path)
     }
-}
\ No newline at end of file
+    fn bin_path(file: Option<&str>) -> String {
+        let file_path = file.map_or("".to_string(), |f| format!("/{}", f));
+        format!("{}/bin{}", Valet::home_path(), file_path)
+    }
+    fn mail_path(file: Option<&str>) -> String {
+        let file_path = file.map_or("".to_string(), |f| format!("/{}", f));
+        format!("{}/Mail{}", Valet::home_path(), file_path)
+    }
+    fn snippets_path(file: Option<&str>) -> String {
+        let file_path = file.map_or("".to_string(), |f| format!("/{}", f));
+        format!("{}/Snippets{}", Valet::home_path(), file_path)
+    }
+}
@@ -1,15 +1,95 @@
+use std::collections::BTreeMap;
+
 use regex::Regex;
-use serde_json::json;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
 
 use crate::configuration::Configuration;
-use crate::constants::{COMMON_EXTENSIONS, FPM_CONFIG_FILE_NAME, group, ISOLATION_SUPPORTED_PHP_VERSIONS, SUPPORTED_PHP_VERSIONS, user, Valet};
+use crate::constants::{COMMON_EXTENSIONS, EDITABLE_PHP_INI_KEYS, FPM_CONFIG_FILE_NAME, FPM_STATUS_PATH, group, ISOLATION_SUPPORTED_PHP_VERSIONS, PHP_INI_FILE_NAME, SUPPORTED_PHP_VERSIONS, user, Valet};
 use crate::devtools::DevTools;
+use crate::fastcgi::FastCgiClient;
+use crate::fpm_status::{FpmStatus, SlowLogEntry};
 use crate::manager::apt::Apt;
 use crate::manager::command::ValetCommandLine;
 use crate::manager::file_system::ValetFilesystem;
 use crate::manager::interface::{CommandLine, Filesystem, PackageManager, ServiceManager};
 use crate::manager::service_manager::ValetServiceManager;
 use crate::nginx::Nginx;
+use crate::nginx_config::NginxConfig;
+use crate::paths::{Paths, PathTrait};
+use crate::stub::Stub;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct FpmPoolSettings {
+    pub pm: String,
+    pub max_children: u32,
+    pub start_servers: u32,
+    pub min_spare_servers: u32,
+    pub max_spare_servers: u32,
+    pub process_idle_timeout: String,
+    pub max_requests: u32,
+    pub request_terminate_timeout: String,
+    pub slowlog: bool,
+    pub request_slowlog_timeout: String,
+    pub status_path: Option<String>,
+}
+
+impl Default for FpmPoolSettings {
+    fn default() -> Self {
+        Self {
+            pm: "dynamic".to_string(),
+            max_children: 5,
+            start_servers: 1,
+            min_spare_servers: 1,
+            max_spare_servers: 1,
+            process_idle_timeout: "10s".to_string(),
+            max_requests: 500,
+            request_terminate_timeout: "0".to_string(),
+            slowlog: false,
+            request_slowlog_timeout: "5s".to_string(),
+            status_path: None,
+        }
+    }
+}
+
+impl FpmPoolSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        if !["static", "dynamic", "ondemand"].contains(&self.pm.as_str()) {
+            return Err(format!("Invalid process manager [{}]. Use static, dynamic or ondemand.", self.pm));
+        }
+        if self.max_children < 1 {
+            return Err("pm.max_children must be greater than 0.".to_string());
+        }
+        if self.pm == "dynamic" {
+            if self.min_spare_servers < 1 || self.min_spare_servers > self.max_spare_servers {
+                return Err("pm.min_spare_servers must be between 1 and pm.max_spare_servers.".to_string());
+            }
+            if self.max_spare_servers > self.max_children {
+                return Err("pm.max_spare_servers must not exceed pm.max_children.".to_string());
+            }
+            if self.start_servers < self.min_spare_servers || self.start_servers > self.max_spare_servers {
+                return Err("pm.start_servers must be between pm.min_spare_servers and pm.max_spare_servers.".to_string());
+            }
+        }
+        let duration = Regex::new(r"^\d+[smhd]?$").unwrap();
+        for (name, value) in [
+            ("pm.process_idle_timeout", &self.process_idle_timeout),
+            ("request_terminate_timeout", &self.request_terminate_timeout),
+            ("request_slowlog_timeout", &self.request_slowlog_timeout),
+        ] {
+            if !duration.is_match(value) {
+                return Err(format!("Invalid duration [{}] for {}.", value, name));
+            }
+        }
+        if let Some(path) = &self.status_path {
+            if !Regex::new(r"^/[A-Za-z0-9_\-/]*$").unwrap().is_match(path) {
+                return Err(format!("Invalid status path [{}].", path));
+            }
+        }
+        Ok(())
+    }
+}
 
 pub struct PhpFpm {
     config: Configuration,
@@ -40,6 +120,10 @@ impl PhpFpm {
     }
 
 
+    pub fn start(&self, version: Option<&str>) {
+        self.sm.start(vec![self.service_name(version).as_str()]);
+    }
+
     pub fn restart(&self, version: Option<&str>) {
         self.sm.restart(vec![self.service_name(version).as_str()]);
     }
@@ -52,12 +136,18 @@ impl PhpFpm {
         self.sm.print_status(&self.service_name(version));
     }
 
+    pub fn is_running(&self, version: Option<&str>) -> bool {
+        self.sm.is_running(&self.service_name(version))
+    }
+
     pub fn socket_file_name(&self, v: Option<&str>) -> String {
         let current_version = self.get_current_version();
         let version = current_version.as_str();
-        let version = v.unwrap_or(version);
-        let version = version.replace(|c: char| !c.is_digit(10), "");
-        format!("valet{}.sock", version)
+        Self::socket_file(v.unwrap_or(version))
+    }
+    // valet83.sock for 8.3, without needing the installed versions
+    pub fn socket_file(version: &str) -> String {
+        format!("valet{}.sock", version.replace(|c: char| !c.is_ascii_digit(), ""))
     }
     pub fn normalize_php_version(&self, version: &str) -> String {
         let re = Regex::new(r"^(?:php[@-]?)?(?P<MAJOR_VERSION>\d{1}).?(?P<MINOR_VERSION>\d{1})$").unwrap();
@@ -77,12 +167,52 @@ impl PhpFpm {
             }
         }
     }
-    pub fn get_php_executable_path(&self, version: Option<&str>) {
+    pub fn get_php_executable_path(&self, version: Option<&str>) -> String {
         let bin = match version {
             Some(v) => format!("php{}", self.normalize_php_version(v)),
             None => "php".to_string(),
         };
-        DevTools::get_bin(&bin, &["/usr/local/bin"])
+        DevTools::get_bin(&bin, &["/usr/local/bin", Paths::bin_path(None).as_str()])
+            .unwrap_or(format!("/usr/bin/{}", bin))
+    }
+
+    // Write `php` and `composer` shims that pick the PHP version per directory
+    pub fn install_cli_shims(&self) -> Result<String, String> {
+        let bin_path = Paths::bin_path(None);
+        self.files.ensure_dir_exists(&bin_path, &user(), 0o755).map_err(|e| e.to_string())?;
+
+        let binaries: Vec<String> = ISOLATION_SUPPORTED_PHP_VERSIONS.iter()
+            .filter_map(|&version| {
+                let bin = format!("php{}", version);
+                DevTools::get_bin(&bin, &[bin_path.as_str()]).map(|path| {
+                    format!("    {}|{}) exec \"{}\" \"$@\" ;;", version, version.replace(".", ""), path)
+                })
+            })
+            .collect();
+        let current_version = self.get_current_version();
+        let php = Stub::load("php.shim")?
+            .set("PHP_BINARIES", &binaries.join("\n"))
+            .set("DEFAULT_PHP", &self.get_php_executable_path(Some(&current_version)))
+            .render()?;
+        self.files.put(&Paths::bin_path(Some("php")), &php).map_err(|e| e.to_string())?;
+        self.files.chmod(&Paths::bin_path(Some("php")), 0o755).map_err(|e| e.to_string())?;
+
+        match DevTools::get_bin(&"composer".to_string(), &[bin_path.as_str()]) {
+            Some(composer) => {
+                let shim = Stub::load("composer.shim")?
+                    .set("BIN_PATH", &bin_path)
+                    .set("COMPOSER", &composer)
+                    .render()?;
+                self.files.put(&Paths::bin_path(Some("composer")), &shim).map_err(|e| e.to_string())?;
+                self.files.chmod(&Paths::bin_path(Some("composer")), 0o755).map_err(|e| e.to_string())?;
+            }
+            None => self.files.unlink(&Paths::bin_path(Some("composer"))).map_err(|e| e.to_string())?,
+        }
+        Ok(format!("export PATH=\"{}:$PATH\"", bin_path))
+    }
+
+    pub fn uninstall_cli_shims(&self) -> Result<(), String> {
+        self.files.remove(&[Paths::bin_path(Some("php")).as_str(), Paths::bin_path(Some("composer")).as_str()]).map_err(|e| e.to_string())
     }
 
     pub fn fpm_socket_file(&self, version: &str) -> String {
@@ -104,13 +234,13 @@ impl PhpFpm {
         }
     }
 
-    pub fn install(&mut self, version: Option<&str>, install_ext: bool) {
+    pub fn install(&self, version: Option<&str>, install_ext: bool) -> Result<(), String> {
         let current_version = self.get_current_version();
         let v = current_version.as_str();
         let version = version.unwrap_or(v);
         let version = self.normalize_php_version(version);
         if version.clone().is_empty() {
-            return;
+            return Ok(());
         }
         let package_name = self.pm.get_php_fpm_name(&version.clone());
         if !self.pm.installed(&package_name) {
@@ -120,11 +250,12 @@ impl PhpFpm {
             }
             self.sm.enable(&self.service_name(Some(&version.clone())));
         }
-        self.files.ensure_dir_exists("/var/log", &user(), 0o775).unwrap();
-        self.install_configuration(&version.clone());
+        self.files.ensure_dir_exists("/var/log", &user(), 0o775).map_err(|e| e.to_string())?;
+        self.install_configuration(&version.clone())?;
         self.restart(Some(&version.clone()));
+        Ok(())
     }
-    pub fn uninstall(&mut self, version: Option<&str>) {
+    pub fn uninstall(&self, version: Option<&str>) {
         let current_version = self.get_current_version();
         let v = current_version.as_str();
         let version = version.unwrap_or(v);
@@ -138,11 +269,11 @@ impl PhpFpm {
             self.stop(Some(&version));
         }
     }
-    pub fn switch_version(&mut self, version: &str, update_cli: bool, ignore_ext: bool) {
+    pub fn switch_version(&mut self, version: &str, update_cli: bool, ignore_ext: bool) -> Result<Vec<String>, String> {
         let current_version = self.get_current_version();
         let version = self.normalize_php_version(version);
         println!("Changing PHP version...");
-        self.install(Some(&version.clone()), !ignore_ext);
+        self.install(Some(&version.clone()), !ignore_ext)?;
 
         if self.sm.disabled(&self.service_name(Some(&version.clone()))) {
             self.sm.enable(&self.service_name(Some(&version.clone())));
@@ -150,12 +281,19 @@ impl PhpFpm {
         self.config.set("php_version", json!(version));
 
         self.stop_if_unused(&current_version);
-        self.update_nginx_config_files(&version);
+        let changed = self.update_nginx_config_files(&version);
+        for path in &changed {
+            println!("Updated PHP socket in {}", path);
+        }
         self.nginx.restart();
         self.status(Some(&version));
         if update_cli {
-            self.cli.run(&format!("update-alternatives --set php /usr/bin/php{}", version)).unwrap();
+            match self.install_cli_shims() {
+                Ok(path_hint) => println!("PHP CLI shims updated, make sure your shell runs: {}", path_hint),
+                Err(error) => eprintln!("Unable to update PHP CLI shims: {}", error),
+            }
         }
+        Ok(changed)
     }
 
     pub fn update_home_path(&self, old_home_path: &str, new_home_path: &str) {
@@ -169,25 +307,201 @@ impl PhpFpm {
         }
     }
 
-    fn update_nginx_config_files(&self, version: &str) {
+    // Point every non-isolated site at the version's socket and return the files that changed
+    fn update_nginx_config_files(&self, version: &str) -> Vec<String> {
         // Action 1: Update all separate secured versions
+        let socket = self.fpm_socket_file(version);
+        let mut changed = Vec::new();
         for file in self.nginx.configured_sites() {
-            let path = format!("{}/Nginx/{}", Valet::home_path(), file);
-            let mut content = self.files.get(&path).unwrap();
-            if content.contains(&format!("# {}", "ISOLATED_PHP_VERSION")) {
-                continue;
-            }
-            if let Some(_) = Regex::new(r"unix:(.*?.sock)").unwrap().captures(&content) {
-                content = content.replace(
-                    "unix:(.*?.sock)",
-                    &format!("unix:{}", self.socket_file_name(Some(version))),
-                );
-                self.files.put(&path, &content).unwrap();
+            let path = Paths::nginx_path(Some(&file));
+            let content = self.files.get(&path).unwrap();
+            match NginxConfig::rewrite_fpm_socket(&content, &socket) {
+                Ok(Some(updated)) => {
+                    self.files.put(&path, &updated).unwrap();
+                    changed.push(path);
+                }
+                Ok(None) => {}
+                Err(error) => eprintln!("Skipping {}: {}", path, error),
             }
         }
         // Action 2: Update NGINX valet.conf for php socket version
         let s_file_name = self.socket_file_name(Some(version));
         self.nginx.install_server(Some(s_file_name.as_str()));
+        changed
+    }
+
+    pub fn pool_settings(&self, version: Option<&str>) -> FpmPoolSettings {
+        let current_version = self.get_current_version();
+        let version = self.normalize_php_version(version.unwrap_or(current_version.as_str()));
+        self.config.get("fpm_pools")
+            .and_then(|pools| pools.get(&version).cloned())
+            .and_then(|settings| serde_json::from_value(settings).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn set_pool_settings(&self, version: Option<&str>, settings: FpmPoolSettings) -> Result<(), String> {
+        let current_version = self.get_current_version();
+        let version = self.normalize_php_version(version.unwrap_or(current_version.as_str()));
+        if version.is_empty() {
+            return Err("Invalid PHP version.".to_string());
+        }
+        settings.validate()?;
+        let previous = self.config.get("fpm_pools").unwrap_or(json!({}));
+        let mut pools = previous.clone();
+        pools[&version] = json!(settings);
+        self.config.set("fpm_pools", pools);
+        if let Err(error) = self.install_configuration(&version) {
+            self.config.set("fpm_pools", previous);
+            return Err(error);
+        }
+        self.restart(Some(&version));
+        Ok(())
+    }
+
+    // Re-render the pool of every installed version, e.g. after a global setting changed
+    pub fn reinstall_configurations(&self) -> Result<(), String> {
+        for version in ISOLATION_SUPPORTED_PHP_VERSIONS.iter() {
+            if !self.pm.installed(&self.pm.get_php_fpm_name(version)) {
+                continue;
+            }
+            self.install_configuration(version)?;
+            self.restart(Some(version));
+        }
+        Ok(())
+    }
+
+    pub fn slowlog_file(&self, version: &str) -> String {
+        format!("{}/Log/php{}-fpm-slow.log", Valet::home_path(), version)
+    }
+
+    // Turn on the pool status page and the slow log for a version
+    pub fn enable_monitoring(&self, version: Option<&str>) -> Result<(), String> {
+        let mut settings = self.pool_settings(version);
+        if settings.status_path.is_some() && settings.slowlog {
+            return Ok(());
+        }
+        settings.status_path.get_or_insert_with(|| FPM_STATUS_PATH.to_string());
+        settings.slowlog = true;
+        self.set_pool_settings(version, settings)
+    }
+
+    // Query the pool status page over the FPM socket
+    pub fn pool_status(&self, version: Option<&str>) -> Result<FpmStatus, String> {
+        let current_version = self.get_current_version();
+        let version = self.normalize_php_version(version.unwrap_or(current_version.as_str()));
+        let status_path = self.pool_settings(Some(&version)).status_path
+            .ok_or(format!("The status page is not enabled for PHP {}.", version))?;
+        let body = FastCgiClient::new(&self.fpm_socket_file(&version))
+            .get(&status_path, "json")
+            .map_err(|e| format!("Unable to reach PHP-FPM {}: {}", version, e))?;
+        FpmStatus::parse(&body)
+    }
+
+    pub fn slow_log(&self, version: Option<&str>) -> Vec<SlowLogEntry> {
+        let current_version = self.get_current_version();
+        let version = self.normalize_php_version(version.unwrap_or(current_version.as_str()));
+        let content = self.files.get(&self.slowlog_file(&version)).unwrap_or_default();
+        SlowLogEntry::parse_log(&content)
+    }
+
+    pub fn ini_overrides(&self, version: Option<&str>) -> BTreeMap<String, String> {
+        let current_version = self.get_current_version();
+        let version = self.normalize_php_version(version.unwrap_or(current_version.as_str()));
+        self.config.get("php_ini")
+            .and_then(|overrides| overrides.get(&version).cloned())
+            .map(|overrides| Self::ini_map(&overrides))
+            .unwrap_or_default()
+    }
+
+    pub fn set_ini_override(&self, version: Option<&str>, key: &str, value: Option<&str>) -> Result<(), String> {
+        let current_version = self.get_current_version();
+        let version = self.normalize_php_version(version.unwrap_or(current_version.as_str()));
+        if version.is_empty() {
+            return Err("Invalid PHP version.".to_string());
+        }
+        let mut overrides = self.config.get("php_ini").unwrap_or(json!({}));
+        match value {
+            Some(value) => {
+                Self::validate_ini_setting(key, value)?;
+                overrides[&version][key] = json!(value);
+            }
+            None => {
+                if let Some(settings) = overrides.get_mut(&version).and_then(|v| v.as_object_mut()) {
+                    settings.remove(key);
+                }
+            }
+        }
+        self.config.set("php_ini", overrides);
+        self.install_ini_overrides(&version)?;
+        self.restart(Some(&version));
+        Ok(())
+    }
+
+    pub fn effective_ini(&self, version: Option<&str>, site_overrides: &BTreeMap<String, String>) -> BTreeMap<String, String> {
+        let current_version = self.get_current_version();
+        let version = self.normalize_php_version(version.unwrap_or(current_version.as_str()));
+        let overrides = self.ini_overrides(Some(&version));
+        let mut values: BTreeMap<String, String> = EDITABLE_PHP_INI_KEYS.iter()
+            .map(|&key| (key.to_string(), String::new()))
+            .chain(overrides.keys().chain(site_overrides.keys()).map(|key| (key.clone(), String::new())))
+            .collect();
+
+        // php-fpm -i prints HTML, so the CLI binary reads the FPM ini files and prints "directive=value"
+        let keys = values.keys().map(|key| format!("\"{}\"", key)).collect::<Vec<String>>().join(", ");
+        let info = self.cli.run(&format!(
+            "PHP_INI_SCAN_DIR=/etc/php/{0}/fpm/conf.d php{0} -c /etc/php/{0}/fpm/php.ini -r 'foreach ([{1}] as $k) echo $k, \"=\", ini_get($k), PHP_EOL;'",
+            version, keys
+        )).unwrap_or_default();
+        for line in info.lines() {
+            if let Some((key, value)) = line.split_once('=') {
+                if let Some(entry) = values.get_mut(key.trim()) {
+                    *entry = value.trim().to_string();
+                }
+            }
+        }
+        values.extend(overrides);
+        values.extend(site_overrides.clone());
+        values
+    }
+
+    pub fn validate_ini_setting(key: &str, value: &str) -> Result<(), String> {
+        let re = Regex::new(r"^[a-z0-9_.]+$").unwrap();
+        if !re.is_match(key) {
+            return Err(format!("Invalid php.ini directive [{}].", key));
+        }
+        if value.contains(['\n', '\r', '"', ';']) {
+            return Err(format!("Invalid value for php.ini directive [{}].", key));
+        }
+        Ok(())
+    }
+
+    // The value of a `fastcgi_param PHP_VALUE` carrying the overrides, one directive per line
+    pub fn php_value(overrides: &BTreeMap<String, String>) -> String {
+        overrides.iter()
+            .map(|(key, value)| format!("{}={}", key, value))
+            .collect::<Vec<String>>()
+            .join("\\n")
+    }
+
+    pub fn ini_map(value: &Value) -> BTreeMap<String, String> {
+        value.as_object()
+            .map(|settings| settings.iter()
+                .filter_map(|(key, value)| value.as_str().map(|v| (key.clone(), v.to_string())))
+                .collect())
+            .unwrap_or_default()
+    }
+
+    fn install_ini_overrides(&self, version: &str) -> Result<(), String> {
+        let ini_path = format!("{}/{}", self.fpm_ini_scan_path(Some(version)), PHP_INI_FILE_NAME);
+        let overrides = self.ini_overrides(Some(version));
+        if overrides.is_empty() {
+            return self.files.unlink(&ini_path).map_err(|e| e.to_string());
+        }
+        let mut contents = String::from("; Managed by valetui, changes made here will be overwritten\n");
+        for (key, value) in overrides {
+            contents.push_str(&format!("{} = {}\n", key, value));
+        }
+        self.files.put(&ini_path, &contents).map_err(|e| format!("Unable to write {}: {}", ini_path, e))
     }
 
     fn install_extensions(&self, version: &str) {
@@ -198,13 +512,47 @@ impl PhpFpm {
         self.pm.ensure_installed(&extensions.join(" "));
     }
 
-    fn install_configuration(&self, version: &str) {
-        let contents = self.files.get(&format!("{}/stubs/fpm.conf", Valet::root_path())).unwrap();
-        let contents = contents.replace("VALET_USER", &user())
-            .replace("VALET_GROUP", group().unwrap().as_str())
-            .replace("VALET_FPM_SOCKET_FILE", &self.fpm_socket_file(version));
+    // Write the pool configuration and keep the previous one when `php-fpm -t` rejects it
+    fn install_configuration(&self, version: &str) -> Result<(), String> {
+        let pool_path = format!("{}/{}", self.fpm_config_path(Some(version)), FPM_CONFIG_FILE_NAME);
+        let previous = self.files.get(&pool_path).ok();
+        self.files.put(&pool_path, &self.render_pool_configuration(version)?)
+            .map_err(|e| format!("Unable to write {}: {}", pool_path, e))?;
+        self.install_ini_overrides(version)?;
 
-        self.files.put(&format!("{}/{}", self.fpm_config_path(Some(version)), FPM_CONFIG_FILE_NAME), &contents).unwrap();
+        if let Err(error) = self.cli.run(&format!("php-fpm{} -t", version)) {
+            match previous {
+                Some(contents) => self.files.put(&pool_path, &contents).map_err(|e| e.to_string())?,
+                None => self.files.unlink(&pool_path).map_err(|e| e.to_string())?,
+            }
+            return Err(format!("PHP-FPM {} rejected the pool configuration: {}", version, error.to_string().trim()));
+        }
+        Ok(())
+    }
+
+    fn render_pool_configuration(&self, version: &str) -> Result<String, String> {
+        let settings = self.pool_settings(Some(version));
+        let sendmail = self.config.get("sendmail_path").and_then(|v| v.as_str().map(|s| s.to_string()));
+        Stub::load("fpm.conf")?
+            .set("USER", &user())
+            .set("GROUP", &group().unwrap())
+            .set("FPM_SOCKET_FILE", &self.fpm_socket_file(version))
+            .set("FPM_PM", &settings.pm)
+            .set("FPM_START_SERVERS", &settings.start_servers.to_string())
+            .set("FPM_MIN_SPARE_SERVERS", &settings.min_spare_servers.to_string())
+            .set("FPM_MAX_SPARE_SERVERS", &settings.max_spare_servers.to_string())
+            .set("FPM_MAX_CHILDREN", &settings.max_children.to_string())
+            .set("FPM_PROCESS_IDLE_TIMEOUT", &settings.process_idle_timeout)
+            .set("FPM_MAX_REQUESTS", &settings.max_requests.to_string())
+            .set("FPM_REQUEST_TERMINATE_TIMEOUT", &settings.request_terminate_timeout)
+            .set("FPM_SLOWLOG", &self.slowlog_file(version))
+            .set("FPM_REQUEST_SLOWLOG_TIMEOUT", &settings.request_slowlog_timeout)
+            .set("FPM_STATUS_PATH", settings.status_path.as_deref().unwrap_or_default())
+            .set("SENDMAIL_PATH", sendmail.as_deref().unwrap_or_default())
+            .flag("status", settings.status_path.is_some())
+            .flag("sendmail", sendmail.is_some())
+            .flag("slowlog", settings.slowlog)
+            .render()
     }
 
     fn utilized_php_versions(&self) -> Vec<String> {
@@ -251,6 +599,29 @@ impl PhpFpm {
         panic!("Unable to determine PHP-FPM configuration folder.");
     }
 
+    fn fpm_ini_scan_path(&self, version: Option<&str>) -> String {
+        let current_version = self.get_current_version();
+        let v = current_version.as_str();
+        let version = version.unwrap_or(v);
+        let version_without_dot = version.replace(".", "");
+        let conf_dirs = vec![
+            format!("/etc/php/{}/fpm/conf.d", version), // Ubuntu
+            format!("/etc/php{}/fpm/conf.d", version), // Ubuntu
+            format!("/etc/php{}/conf.d", version), // Manjaro
+            format!("/etc/php{}/conf.d", version_without_dot), // ArchLinux
+            "/etc/php7/conf.d".to_string(), // openSUSE PHP7
+            "/etc/php8/conf.d".to_string(), // openSUSE PHP8
+            "/etc/php.d".to_string(), // Fedora
+            "/etc/php/conf.d".to_string(), // Arch
+        ];
+        for path in conf_dirs {
+            if self.files.is_dir(&path) {
+                return path;
+            }
+        }
+        panic!("Unable to determine PHP-FPM ini scan folder.");
+    }
+
     fn validate_isolation_version(&self, version: &str) {
         if !ISOLATION_SUPPORTED_PHP_VERSIONS.contains(&version) {
             panic!(
@@ -0,0 +1,47 @@
+use crate::dnsmasq::DnsMasq;
+use crate::services::{Service, ServiceState, state};
+
+impl Service for DnsMasq {
+    fn name(&self) -> &'static str {
+        "dnsmasq"
+    }
+
+    fn label(&self) -> &'static str {
+        "DnsMasq"
+    }
+
+    fn install(&self) -> Result<(), String> {
+        DnsMasq::install(self, &self.domains()).map_err(|e| e.to_string())
+    }
+
+    fn uninstall(&self) -> Result<(), String> {
+        DnsMasq::uninstall(self).map_err(|e| e.to_string())
+    }
+
+    fn start(&self) -> Result<(), String> {
+        DnsMasq::start(self);
+        Ok(())
+    }
+
+    fn stop(&self) -> Result<(), String> {
+        DnsMasq::stop(self);
+        Ok(())
+    }
+
+    fn restart(&self) -> Result<(), String> {
+        DnsMasq::restart(self);
+        Ok(())
+    }
+
+    fn status(&self) -> ServiceState {
+        state(self.is_running())
+    }
+
+    fn ports(&self) -> Vec<u16> {
+        vec![53]
+    }
+
+    fn log_files(&self) -> Vec<String> {
+        vec![DnsMasq::log_file()]
+    }
+}
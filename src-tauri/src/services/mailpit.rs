@@ -0,0 +1,48 @@
+use crate::mailpit::Mailpit;
+use crate::services::{Service, ServiceState, state};
+
+impl Service for Mailpit {
+    fn name(&self) -> &'static str {
+        "mailpit"
+    }
+
+    fn label(&self) -> &'static str {
+        "Mailpit"
+    }
+
+    fn install(&self) -> Result<(), String> {
+        Mailpit::install(self)
+    }
+
+    fn uninstall(&self) -> Result<(), String> {
+        Mailpit::uninstall(self)
+    }
+
+    fn start(&self) -> Result<(), String> {
+        Mailpit::start(self);
+        Ok(())
+    }
+
+    fn stop(&self) -> Result<(), String> {
+        Mailpit::stop(self);
+        Ok(())
+    }
+
+    fn restart(&self) -> Result<(), String> {
+        Mailpit::restart(self);
+        Ok(())
+    }
+
+    fn status(&self) -> ServiceState {
+        state(self.is_running())
+    }
+
+    // SMTP and the web UI
+    fn ports(&self) -> Vec<u16> {
+        vec![1025, 8025]
+    }
+
+    fn log_files(&self) -> Vec<String> {
+        vec!["/opt/valet-linux/mailpit.log".to_string()]
+    }
+}
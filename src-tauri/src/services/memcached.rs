@@ -0,0 +1,209 @@
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpStream;
+use std::time::Duration;
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::configuration::Configuration;
+use crate::manager::apt::Apt;
+use crate::manager::file_system::ValetFilesystem;
+use crate::manager::interface::{Filesystem, PackageManager, ServiceManager};
+use crate::manager::service_manager::ValetServiceManager;
+use crate::services::{CacheStats, Service, ServiceState, state};
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct MemcachedSettings {
+    pub port: u16,
+    // Memory limit in megabytes
+    pub memory: u32,
+}
+
+impl Default for MemcachedSettings {
+    fn default() -> Self {
+        Self { port: 11211, memory: 64 }
+    }
+}
+
+pub struct Memcached {
+    pm: Apt,
+    sm: ValetServiceManager,
+    files: ValetFilesystem,
+    config: Configuration,
+    memcached_conf: String,
+}
+
+impl Memcached {
+    const SERVICE_NAME: &'static str = "memcached";
+
+    pub fn new(pm: Apt, sm: ValetServiceManager, files: ValetFilesystem, config: Configuration) -> Self {
+        Self {
+            pm,
+            sm,
+            files,
+            config,
+            memcached_conf: "/etc/memcached.conf".to_string(),
+        }
+    }
+
+    pub fn install(&self) {
+        self.pm.ensure_installed(&self.pm.package_name("memcached"));
+        self.sm.enable(Self::SERVICE_NAME);
+        self.install_configuration();
+        self.restart();
+    }
+
+    pub fn start(&self) {
+        self.sm.start(vec![Self::SERVICE_NAME]);
+    }
+
+    pub fn restart(&self) {
+        self.sm.restart(vec![Self::SERVICE_NAME]);
+    }
+
+    pub fn stop(&self) {
+        self.sm.stop(vec![Self::SERVICE_NAME]);
+    }
+
+    pub fn status(&self) {
+        self.sm.print_status(Self::SERVICE_NAME);
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.sm.is_running(Self::SERVICE_NAME)
+    }
+
+    pub fn uninstall(&self) {
+        self.stop();
+        self.files.restore(&self.memcached_conf).unwrap();
+    }
+
+    pub fn settings(&self) -> MemcachedSettings {
+        self.config.get("memcached")
+            .and_then(|settings| serde_json::from_value(settings).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn set_settings(&self, settings: MemcachedSettings) -> Result<(), String> {
+        if settings.port == 0 || settings.memory == 0 {
+            return Err("Memcached port and memory must be greater than 0.".to_string());
+        }
+        self.config.set("memcached", json!(settings));
+        self.install_configuration();
+        self.restart();
+        Ok(())
+    }
+
+    pub fn flush(&self) -> Result<(), String> {
+        let response = self.command("flush_all")?;
+        match response.first().map(|line| line.as_str()) {
+            Some("OK") => Ok(()),
+            other => Err(format!("Unexpected memcached response: {:?}", other)),
+        }
+    }
+
+    pub fn stats(&self) -> Result<CacheStats, String> {
+        let mut stats = CacheStats::default();
+        for line in self.command("stats")? {
+            let mut parts = line.split_whitespace().skip(1);
+            let (Some(key), Some(value)) = (parts.next(), parts.next()) else {
+                continue;
+            };
+            match key {
+                "curr_items" => stats.keys = value.parse().unwrap_or_default(),
+                "bytes" => stats.memory_bytes = value.parse().unwrap_or_default(),
+                "limit_maxbytes" => stats.max_memory_bytes = value.parse().unwrap_or_default(),
+                _ => {}
+            }
+        }
+        Ok(stats)
+    }
+
+    // Rewrite the port and memory flags of the Debian-style option file
+    fn install_configuration(&self) {
+        let settings = self.settings();
+        self.files.backup(&self.memcached_conf).unwrap();
+        let contents = self.files.get(&self.memcached_conf).unwrap_or_default();
+        let mut contents = Self::set_option(&contents, "p", &settings.port.to_string());
+        contents = Self::set_option(&contents, "m", &settings.memory.to_string());
+        self.files.put(&self.memcached_conf, &contents).unwrap();
+    }
+
+    fn set_option(contents: &str, flag: &str, value: &str) -> String {
+        let re = Regex::new(&format!(r"(?m)^-{}\s+\S+$", flag)).unwrap();
+        let line = format!("-{} {}", flag, value);
+        if re.is_match(contents) {
+            re.replace_all(contents, line.as_str()).to_string()
+        } else {
+            format!("{}\n{}\n", contents.trim_end(), line)
+        }
+    }
+
+    // Send a text protocol command and collect the lines up to the terminator
+    fn command(&self, command: &str) -> Result<Vec<String>, String> {
+        let address = format!("127.0.0.1:{}", self.settings().port);
+        let mut stream = TcpStream::connect(&address).map_err(|e| format!("Unable to reach memcached on {}: {}", address, e))?;
+        stream.set_read_timeout(Some(Duration::from_secs(3))).map_err(|e| e.to_string())?;
+        stream.write_all(format!("{}\r\n", command).as_bytes()).map_err(|e| e.to_string())?;
+
+        let mut lines = Vec::new();
+        for line in BufReader::new(stream).lines() {
+            let line = line.map_err(|e| e.to_string())?;
+            let done = matches!(line.as_str(), "END" | "OK" | "ERROR") || line.starts_with("SERVER_ERROR");
+            lines.push(line);
+            if done {
+                break;
+            }
+        }
+        Ok(lines)
+    }
+}
+
+impl Service for Memcached {
+    fn name(&self) -> &'static str {
+        "memcached"
+    }
+
+    fn label(&self) -> &'static str {
+        "Memcached"
+    }
+
+    fn install(&self) -> Result<(), String> {
+        Memcached::install(self);
+        Ok(())
+    }
+
+    fn uninstall(&self) -> Result<(), String> {
+        Memcached::uninstall(self);
+        Ok(())
+    }
+
+    fn start(&self) -> Result<(), String> {
+        Memcached::start(self);
+        Ok(())
+    }
+
+    fn stop(&self) -> Result<(), String> {
+        Memcached::stop(self);
+        Ok(())
+    }
+
+    fn restart(&self) -> Result<(), String> {
+        Memcached::restart(self);
+        Ok(())
+    }
+
+    fn status(&self) -> ServiceState {
+        state(self.is_running())
+    }
+
+    fn ports(&self) -> Vec<u16> {
+        vec![self.settings().port]
+    }
+
+    fn log_files(&self) -> Vec<String> {
+        vec!["/var/log/memcached.log".to_string()]
+    }
+}
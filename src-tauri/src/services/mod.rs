@@ -0,0 +1,80 @@
+use std::net::{SocketAddr, TcpStream};
+use std::time::Duration;
+
+use serde::Serialize;
+
+pub mod registry;
+pub mod nginx;
+pub mod php;
+pub mod dnsmasq;
+pub mod mailpit;
+pub mod mysql;
+pub mod postgres;
+pub mod redis;
+pub mod memcached;
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ServiceState {
+    Running,
+    Stopped,
+}
+
+// What the UI and tray need to show a service
+#[derive(Serialize, Clone, Debug)]
+pub struct ServiceInfo {
+    pub name: String,
+    pub label: String,
+    pub state: ServiceState,
+    pub ports: Vec<u16>,
+    pub log_files: Vec<String>,
+}
+
+// Common surface of every service valetui manages
+pub trait Service {
+    fn name(&self) -> &'static str;
+    fn label(&self) -> &'static str;
+    fn install(&self) -> Result<(), String>;
+    fn uninstall(&self) -> Result<(), String>;
+    fn start(&self) -> Result<(), String>;
+    fn stop(&self) -> Result<(), String>;
+    fn restart(&self) -> Result<(), String>;
+    fn status(&self) -> ServiceState;
+    fn ports(&self) -> Vec<u16>;
+    fn log_files(&self) -> Vec<String>;
+
+    // A service is healthy when it runs and accepts connections on its local ports
+    fn health_check(&self) -> Result<(), String> {
+        if self.status() != ServiceState::Running {
+            return Err(format!("{} is not running.", self.label()));
+        }
+        for port in self.ports() {
+            let address = SocketAddr::from(([127, 0, 0, 1], port));
+            TcpStream::connect_timeout(&address, Duration::from_secs(2))
+                .map_err(|e| format!("{} does not accept connections on port {}: {}", self.label(), port, e))?;
+        }
+        Ok(())
+    }
+
+    fn info(&self) -> ServiceInfo {
+        ServiceInfo {
+            name: self.name().to_string(),
+            label: self.label().to_string(),
+            state: self.status(),
+            ports: self.ports(),
+            log_files: self.log_files(),
+        }
+    }
+}
+
+// Dashboard readout shared by the cache services
+#[derive(Serialize, Clone, Debug, Default)]
+pub struct CacheStats {
+    pub keys: u64,
+    pub memory_bytes: u64,
+    pub max_memory_bytes: u64,
+}
+
+pub(crate) fn state(running: bool) -> ServiceState {
+    if running { ServiceState::Running } else { ServiceState::Stopped }
+}
@@ -0,0 +1,213 @@
+use regex::Regex;
+use serde_json::json;
+
+use crate::configuration::Configuration;
+use crate::constants::user;
+use crate::manager::apt::Apt;
+use crate::manager::command::ValetCommandLine;
+use crate::manager::file_system::ValetFilesystem;
+use crate::manager::interface::{CommandLine, Filesystem, PackageManager, ServiceManager};
+use crate::manager::service_manager::ValetServiceManager;
+use crate::services::{Service, ServiceState, state};
+
+pub struct Mysql {
+    pm: Apt,
+    sm: ValetServiceManager,
+    cli: ValetCommandLine,
+    files: ValetFilesystem,
+    config: Configuration,
+}
+
+impl Mysql {
+    const FLAVORS: [&'static str; 2] = ["mysql", "mariadb"];
+    const SYSTEM_DATABASES: [&'static str; 4] = ["information_schema", "mysql", "performance_schema", "sys"];
+
+    pub fn new(pm: Apt, sm: ValetServiceManager, cli: ValetCommandLine, files: ValetFilesystem, config: Configuration) -> Self {
+        Self { pm, sm, cli, files, config }
+    }
+
+    // Install MySQL or MariaDB and give the desktop user a passwordless socket login
+    pub fn install(&self, flavor: &str) -> Result<(), String> {
+        if !Self::FLAVORS.contains(&flavor) {
+            return Err(format!("Unknown database server [{}]. Use mysql or mariadb.", flavor));
+        }
+        // Checked on the packages, uninstall only stops the server and forgets the flavor
+        for other in Self::FLAVORS.iter().filter(|other| **other != flavor) {
+            if self.pm.installed(&self.pm.package_name(other)) {
+                return Err(format!("{} is already installed, remove its package first.", other));
+            }
+        }
+        self.pm.ensure_installed(&self.pm.package_name(flavor));
+        self.config.set("database", json!(flavor));
+        self.sm.enable(&self.service_name());
+        self.start();
+        self.configure_user(flavor)
+    }
+
+    pub fn uninstall(&self) {
+        self.stop();
+        self.config.set("database", serde_json::Value::Null);
+    }
+
+    pub fn start(&self) {
+        self.sm.start(vec![self.service_name().as_str()]);
+    }
+
+    pub fn stop(&self) {
+        self.sm.stop(vec![self.service_name().as_str()]);
+    }
+
+    pub fn restart(&self) {
+        self.sm.restart(vec![self.service_name().as_str()]);
+    }
+
+    pub fn status(&self) {
+        self.sm.print_status(&self.service_name());
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.sm.is_running(self.service_name().as_str())
+    }
+
+    pub fn installed_flavor(&self) -> Option<String> {
+        self.config.get("database").and_then(|v| v.as_str().map(|s| s.to_string()))
+    }
+
+    pub fn databases(&self) -> Result<Vec<String>, String> {
+        let output = self.query("SHOW DATABASES")?;
+        Ok(output.lines()
+            .map(|line| line.trim().to_string())
+            .filter(|name| !name.is_empty() && !Self::SYSTEM_DATABASES.contains(&name.as_str()))
+            .collect())
+    }
+
+    pub fn create(&self, name: &str) -> Result<(), String> {
+        Self::validate_name(name)?;
+        self.query(&format!("CREATE DATABASE IF NOT EXISTS `{}` CHARACTER SET utf8mb4 COLLATE utf8mb4_unicode_ci", name))?;
+        Ok(())
+    }
+
+    pub fn drop(&self, name: &str) -> Result<(), String> {
+        Self::validate_name(name)?;
+        if Self::SYSTEM_DATABASES.contains(&name) {
+            return Err(format!("Refusing to drop system database [{}].", name));
+        }
+        self.query(&format!("DROP DATABASE IF EXISTS `{}`", name))?;
+        Ok(())
+    }
+
+    // Import a plain or gzipped SQL dump into the database, creating it when missing
+    pub fn import(&self, name: &str, file: &str) -> Result<(), String> {
+        Self::validate_name(name)?;
+        Self::validate_path(file)?;
+        if !self.files.exists(file) {
+            return Err(format!("Dump file [{}] does not exist.", file));
+        }
+        self.create(name)?;
+        let pipeline = if file.ends_with(".gz") {
+            format!("gunzip -c \"{}\" | mysql \"{}\"", file, name)
+        } else {
+            format!("mysql \"{}\" < \"{}\"", name, file)
+        };
+        self.cli.run_as_user(&format!("bash -o pipefail -c '{}'", pipeline)).map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    // Export the database to a dump, gzipped when the file name ends in .gz
+    pub fn export(&self, name: &str, file: &str) -> Result<(), String> {
+        Self::validate_name(name)?;
+        Self::validate_path(file)?;
+        let pipeline = if file.ends_with(".gz") {
+            format!("mysqldump --single-transaction --routines \"{}\" | gzip > \"{}\"", name, file)
+        } else {
+            format!("mysqldump --single-transaction --routines \"{}\" > \"{}\"", name, file)
+        };
+        // pipefail, otherwise gzip's status hides a failed dump
+        if let Err(error) = self.cli.run_as_user(&format!("bash -o pipefail -c '{}'", pipeline)) {
+            let _ = self.files.unlink(file);
+            return Err(error.to_string());
+        }
+        Ok(())
+    }
+
+    fn configure_user(&self, flavor: &str) -> Result<(), String> {
+        let plugin = if flavor == "mariadb" { "IDENTIFIED VIA unix_socket" } else { "IDENTIFIED WITH auth_socket" };
+        let sql = format!(
+            "CREATE USER IF NOT EXISTS '{user}'@'localhost' {plugin}; GRANT ALL PRIVILEGES ON *.* TO '{user}'@'localhost' WITH GRANT OPTION; FLUSH PRIVILEGES;",
+            user = user(),
+            plugin = plugin,
+        );
+        self.cli.run(&format!("sudo mysql -e \"{}\"", sql)).map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    // Single quotes keep the shell away from the backtick-quoted identifiers
+    fn query(&self, sql: &str) -> Result<String, String> {
+        self.cli.run_as_user(&format!("mysql -N -B -e '{}'", sql)).map_err(|e| e.to_string())
+    }
+
+    fn service_name(&self) -> String {
+        self.installed_flavor().unwrap_or("mysql".to_string())
+    }
+
+    fn validate_name(name: &str) -> Result<(), String> {
+        if !Regex::new(r"^[A-Za-z0-9_\-]{1,64}$").unwrap().is_match(name) {
+            return Err(format!("Invalid database name [{}].", name));
+        }
+        Ok(())
+    }
+
+    fn validate_path(path: &str) -> Result<(), String> {
+        if path.contains(['\'', '"', '`', '$']) {
+            return Err(format!("Unsupported characters in path [{}].", path));
+        }
+        Ok(())
+    }
+}
+
+impl Service for Mysql {
+    fn name(&self) -> &'static str {
+        "mysql"
+    }
+
+    fn label(&self) -> &'static str {
+        "MySQL"
+    }
+
+    fn install(&self) -> Result<(), String> {
+        let flavor = self.installed_flavor().unwrap_or("mysql".to_string());
+        Mysql::install(self, &flavor)
+    }
+
+    fn uninstall(&self) -> Result<(), String> {
+        Mysql::uninstall(self);
+        Ok(())
+    }
+
+    fn start(&self) -> Result<(), String> {
+        Mysql::start(self);
+        Ok(())
+    }
+
+    fn stop(&self) -> Result<(), String> {
+        Mysql::stop(self);
+        Ok(())
+    }
+
+    fn restart(&self) -> Result<(), String> {
+        Mysql::restart(self);
+        Ok(())
+    }
+
+    fn status(&self) -> ServiceState {
+        state(self.is_running())
+    }
+
+    fn ports(&self) -> Vec<u16> {
+        vec![3306]
+    }
+
+    fn log_files(&self) -> Vec<String> {
+        vec!["/var/log/mysql/error.log".to_string()]
+    }
+}
@@ -0,0 +1,53 @@
+use crate::constants::Valet;
+use crate::nginx::Nginx;
+use crate::services::{Service, ServiceState, state};
+
+impl Service for Nginx {
+    fn name(&self) -> &'static str {
+        "nginx"
+    }
+
+    fn label(&self) -> &'static str {
+        "Nginx"
+    }
+
+    fn install(&self) -> Result<(), String> {
+        Nginx::install(self);
+        Ok(())
+    }
+
+    fn uninstall(&self) -> Result<(), String> {
+        Nginx::uninstall(self);
+        Ok(())
+    }
+
+    fn start(&self) -> Result<(), String> {
+        Nginx::start(self);
+        Ok(())
+    }
+
+    fn stop(&self) -> Result<(), String> {
+        Nginx::stop(self);
+        Ok(())
+    }
+
+    fn restart(&self) -> Result<(), String> {
+        Nginx::restart(self);
+        Ok(())
+    }
+
+    fn status(&self) -> ServiceState {
+        state(self.is_running())
+    }
+
+    fn ports(&self) -> Vec<u16> {
+        Nginx::ports(self)
+    }
+
+    fn log_files(&self) -> Vec<String> {
+        vec![
+            format!("{}/Log/nginx-error.log", Valet::home_path()),
+            "/var/log/nginx/error.log".to_string(),
+        ]
+    }
+}
@@ -0,0 +1,62 @@
+use crate::php_fpm::PhpFpm;
+use crate::services::{Service, ServiceState, state};
+
+// Controls the pool of the current PHP version
+impl Service for PhpFpm {
+    fn name(&self) -> &'static str {
+        "php-fpm"
+    }
+
+    fn label(&self) -> &'static str {
+        "PHP-FPM"
+    }
+
+    fn install(&self) -> Result<(), String> {
+        PhpFpm::install(self, None, true)
+    }
+
+    fn uninstall(&self) -> Result<(), String> {
+        PhpFpm::uninstall(self, None);
+        self.uninstall_cli_shims()
+    }
+
+    fn start(&self) -> Result<(), String> {
+        PhpFpm::start(self, None);
+        Ok(())
+    }
+
+    fn stop(&self) -> Result<(), String> {
+        PhpFpm::stop(self, None);
+        Ok(())
+    }
+
+    fn restart(&self) -> Result<(), String> {
+        PhpFpm::restart(self, None);
+        Ok(())
+    }
+
+    fn status(&self) -> ServiceState {
+        state(self.is_running(None))
+    }
+
+    // FPM listens on a Unix socket only
+    fn ports(&self) -> Vec<u16> {
+        vec![]
+    }
+
+    fn log_files(&self) -> Vec<String> {
+        let version = self.get_current_version();
+        vec![format!("/var/log/php{}-fpm.log", version), self.slowlog_file(&version)]
+    }
+
+    fn health_check(&self) -> Result<(), String> {
+        if !self.is_running(None) {
+            return Err("PHP-FPM is not running.".to_string());
+        }
+        let socket = self.fpm_socket_file(&self.get_current_version());
+        if !std::path::Path::new(&socket).exists() {
+            return Err(format!("PHP-FPM socket {} is missing.", socket));
+        }
+        Ok(())
+    }
+}
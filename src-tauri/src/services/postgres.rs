@@ -0,0 +1,185 @@
+use regex::Regex;
+use serde_json::json;
+
+use crate::configuration::Configuration;
+use crate::constants::user;
+use crate::manager::apt::Apt;
+use crate::manager::command::ValetCommandLine;
+use crate::manager::interface::{CommandLine, PackageManager, ServiceManager};
+use crate::manager::service_manager::ValetServiceManager;
+use crate::services::{Service, ServiceState, state};
+
+pub struct Postgres {
+    pm: Apt,
+    sm: ValetServiceManager,
+    cli: ValetCommandLine,
+    config: Configuration,
+}
+
+impl Postgres {
+    const SERVICE_NAME: &'static str = "postgresql";
+    const SYSTEM_DATABASES: [&'static str; 3] = ["postgres", "template0", "template1"];
+
+    pub fn new(pm: Apt, sm: ValetServiceManager, cli: ValetCommandLine, config: Configuration) -> Self {
+        Self { pm, sm, cli, config }
+    }
+
+    // Install the server and create a superuser role for the desktop user
+    pub fn install(&self) -> Result<(), String> {
+        self.pm.ensure_installed(&self.pm.package_name("postgresql"));
+        self.sm.enable(Self::SERVICE_NAME);
+        self.start();
+        self.create_user_role()
+    }
+
+    // Stops and disables the server, the package and its databases stay in place
+    pub fn uninstall(&self) {
+        self.stop();
+        self.sm.disable(Self::SERVICE_NAME);
+    }
+
+    pub fn start(&self) {
+        self.sm.start(vec![Self::SERVICE_NAME]);
+    }
+
+    pub fn stop(&self) {
+        self.sm.stop(vec![Self::SERVICE_NAME]);
+    }
+
+    pub fn restart(&self) {
+        self.sm.restart(vec![Self::SERVICE_NAME]);
+    }
+
+    pub fn status(&self) {
+        self.sm.print_status(Self::SERVICE_NAME);
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.sm.is_running(Self::SERVICE_NAME)
+    }
+
+    pub fn databases(&self) -> Result<Vec<String>, String> {
+        let output = self.psql("SELECT datname FROM pg_database WHERE NOT datistemplate")?;
+        Ok(output.lines()
+            .map(|line| line.trim().to_string())
+            .filter(|name| !name.is_empty() && !Self::SYSTEM_DATABASES.contains(&name.as_str()))
+            .collect())
+    }
+
+    pub fn create(&self, name: &str) -> Result<(), String> {
+        Self::validate_name(name)?;
+        if self.databases()?.iter().any(|db| db == name) {
+            return Ok(());
+        }
+        self.cli.run_as_user(&format!("createdb \"{}\"", name)).map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    pub fn drop(&self, name: &str) -> Result<(), String> {
+        Self::validate_name(name)?;
+        if Self::SYSTEM_DATABASES.contains(&name) {
+            return Err(format!("Refusing to drop system database [{}].", name));
+        }
+        self.cli.run_as_user(&format!("dropdb --if-exists \"{}\"", name)).map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    pub fn creates_site_databases(&self) -> bool {
+        self.config.get("postgres_site_databases").and_then(|v| v.as_bool()).unwrap_or(false)
+    }
+
+    pub fn set_creates_site_databases(&self, enabled: bool) {
+        self.config.set("postgres_site_databases", json!(enabled));
+    }
+
+    // Create a database named after a freshly linked site, when enabled
+    pub fn site_linked(&self, site: &str) -> Result<(), String> {
+        if !self.creates_site_databases() {
+            return Ok(());
+        }
+        self.create(&Self::database_name(site))
+    }
+
+    pub fn database_name(site: &str) -> String {
+        site.to_lowercase()
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect()
+    }
+
+    fn create_user_role(&self) -> Result<(), String> {
+        let exists = self.cli.run(&format!(
+            "sudo -u postgres psql -tAc \"SELECT 1 FROM pg_roles WHERE rolname='{}'\"",
+            user()
+        )).map_err(|e| e.to_string())?;
+        if exists.trim() == "1" {
+            return Ok(());
+        }
+        self.cli.run(&format!("sudo -u postgres createuser --superuser \"{}\"", user())).map_err(|e| e.to_string())?;
+        self.cli.run(&format!("sudo -u postgres createdb --owner \"{0}\" \"{0}\"", user())).map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    fn psql(&self, sql: &str) -> Result<String, String> {
+        self.cli.run_as_user(&format!("psql -d postgres -tAc \"{}\"", sql)).map_err(|e| e.to_string())
+    }
+
+    fn validate_name(name: &str) -> Result<(), String> {
+        if !Regex::new(r"^[A-Za-z0-9_\-]{1,63}$").unwrap().is_match(name) {
+            return Err(format!("Invalid database name [{}].", name));
+        }
+        Ok(())
+    }
+}
+
+impl Service for Postgres {
+    fn name(&self) -> &'static str {
+        "postgresql"
+    }
+
+    fn label(&self) -> &'static str {
+        "PostgreSQL"
+    }
+
+    fn install(&self) -> Result<(), String> {
+        Postgres::install(self)
+    }
+
+    fn uninstall(&self) -> Result<(), String> {
+        Postgres::uninstall(self);
+        Ok(())
+    }
+
+    fn start(&self) -> Result<(), String> {
+        Postgres::start(self);
+        Ok(())
+    }
+
+    fn stop(&self) -> Result<(), String> {
+        Postgres::stop(self);
+        Ok(())
+    }
+
+    fn restart(&self) -> Result<(), String> {
+        Postgres::restart(self);
+        Ok(())
+    }
+
+    fn status(&self) -> ServiceState {
+        state(self.is_running())
+    }
+
+    fn ports(&self) -> Vec<u16> {
+        vec![5432]
+    }
+
+    fn log_files(&self) -> Vec<String> {
+        std::fs::read_dir("/var/log/postgresql")
+            .map(|entries| entries
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.path().to_string_lossy().to_string())
+                .filter(|path| path.ends_with(".log"))
+                .collect())
+            .unwrap_or_default()
+    }
+}
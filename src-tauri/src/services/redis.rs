@@ -0,0 +1,194 @@
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::configuration::Configuration;
+use crate::manager::apt::Apt;
+use crate::manager::command::ValetCommandLine;
+use crate::manager::file_system::ValetFilesystem;
+use crate::manager::interface::{CommandLine, Filesystem, PackageManager, ServiceManager};
+use crate::manager::service_manager::ValetServiceManager;
+use crate::services::{CacheStats, Service, ServiceState, state};
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct RedisSettings {
+    pub port: u16,
+    // Memory limit in megabytes, 0 means unlimited
+    pub max_memory: u32,
+}
+
+impl Default for RedisSettings {
+    fn default() -> Self {
+        Self { port: 6379, max_memory: 128 }
+    }
+}
+
+pub struct Redis {
+    pm: Apt,
+    sm: ValetServiceManager,
+    cli: ValetCommandLine,
+    files: ValetFilesystem,
+    config: Configuration,
+    redis_conf: String,
+    valet_conf: String,
+}
+
+impl Redis {
+    const SERVICE_NAME: &'static str = "redis-server";
+
+    pub fn new(pm: Apt, sm: ValetServiceManager, cli: ValetCommandLine, files: ValetFilesystem, config: Configuration) -> Self {
+        Self {
+            pm,
+            sm,
+            cli,
+            files,
+            config,
+            redis_conf: "/etc/redis/redis.conf".to_string(),
+            valet_conf: "/etc/redis/valet.conf".to_string(),
+        }
+    }
+
+    pub fn install(&self) {
+        self.pm.ensure_installed(&self.pm.package_name("redis"));
+        self.sm.enable(Self::SERVICE_NAME);
+        self.install_configuration();
+        self.restart();
+    }
+
+    pub fn start(&self) {
+        self.sm.start(vec![Self::SERVICE_NAME]);
+    }
+
+    pub fn restart(&self) {
+        self.sm.restart(vec![Self::SERVICE_NAME]);
+    }
+
+    pub fn stop(&self) {
+        self.sm.stop(vec![Self::SERVICE_NAME]);
+    }
+
+    pub fn status(&self) {
+        self.sm.print_status(Self::SERVICE_NAME);
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.sm.is_running(Self::SERVICE_NAME)
+    }
+
+    pub fn uninstall(&self) {
+        self.stop();
+        self.files.unlink(&self.valet_conf).unwrap();
+        self.files.restore(&self.redis_conf).unwrap();
+    }
+
+    pub fn settings(&self) -> RedisSettings {
+        self.config.get("redis")
+            .and_then(|settings| serde_json::from_value(settings).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn set_settings(&self, settings: RedisSettings) -> Result<(), String> {
+        if settings.port == 0 {
+            return Err("Invalid Redis port.".to_string());
+        }
+        self.config.set("redis", json!(settings));
+        self.install_configuration();
+        self.restart();
+        Ok(())
+    }
+
+    // Remove every key from every database
+    pub fn flush(&self) -> Result<(), String> {
+        self.redis_cli("FLUSHALL").map(|_| ())
+    }
+
+    pub fn stats(&self) -> Result<CacheStats, String> {
+        let info = self.redis_cli("INFO")?;
+        let mut stats = CacheStats::default();
+        for line in info.lines() {
+            let Some((key, value)) = line.trim().split_once(':') else {
+                continue;
+            };
+            match key {
+                "used_memory" => stats.memory_bytes = value.parse().unwrap_or_default(),
+                "maxmemory" => stats.max_memory_bytes = value.parse().unwrap_or_default(),
+                // Keyspace lines look like "db0:keys=12,expires=0,avg_ttl=0"
+                db if db.starts_with("db") => {
+                    stats.keys += value.split(',')
+                        .find_map(|pair| pair.strip_prefix("keys="))
+                        .and_then(|keys| keys.parse::<u64>().ok())
+                        .unwrap_or_default();
+                }
+                _ => {}
+            }
+        }
+        Ok(stats)
+    }
+
+    // Our overrides live in their own file, included last from redis.conf
+    fn install_configuration(&self) {
+        let settings = self.settings();
+        self.files.backup(&self.redis_conf).unwrap();
+        let include = format!("include {}", self.valet_conf);
+        let redis_conf = self.files.get(&self.redis_conf).unwrap_or_default();
+        if !redis_conf.lines().any(|line| line.trim() == include) {
+            self.files.append(&self.redis_conf, &format!("\n{}\n", include)).unwrap();
+        }
+        let contents = format!(
+            "# Managed by valetui\nport {}\nmaxmemory {}mb\nmaxmemory-policy allkeys-lru\n",
+            settings.port, settings.max_memory
+        );
+        self.files.put(&self.valet_conf, &contents).unwrap();
+    }
+
+    fn redis_cli(&self, command: &str) -> Result<String, String> {
+        self.cli.run(&format!("redis-cli -p {} {}", self.settings().port, command)).map_err(|e| e.to_string())
+    }
+}
+
+impl Service for Redis {
+    fn name(&self) -> &'static str {
+        "redis"
+    }
+
+    fn label(&self) -> &'static str {
+        "Redis"
+    }
+
+    fn install(&self) -> Result<(), String> {
+        Redis::install(self);
+        Ok(())
+    }
+
+    fn uninstall(&self) -> Result<(), String> {
+        Redis::uninstall(self);
+        Ok(())
+    }
+
+    fn start(&self) -> Result<(), String> {
+        Redis::start(self);
+        Ok(())
+    }
+
+    fn stop(&self) -> Result<(), String> {
+        Redis::stop(self);
+        Ok(())
+    }
+
+    fn restart(&self) -> Result<(), String> {
+        Redis::restart(self);
+        Ok(())
+    }
+
+    fn status(&self) -> ServiceState {
+        state(self.is_running())
+    }
+
+    fn ports(&self) -> Vec<u16> {
+        vec![self.settings().port]
+    }
+
+    fn log_files(&self) -> Vec<String> {
+        vec!["/var/log/redis/redis-server.log".to_string()]
+    }
+}
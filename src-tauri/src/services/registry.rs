@@ -0,0 +1,47 @@
+use crate::services::{Service, ServiceInfo};
+
+// Ordered set of services the UI, tray and commands can enumerate and control
+#[derive(Default)]
+pub struct ServiceRegistry {
+    services: Vec<Box<dyn Service>>,
+}
+
+impl ServiceRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn register(mut self, service: Box<dyn Service>) -> Self {
+        self.services.push(service);
+        self
+    }
+
+    pub fn all(&self) -> &[Box<dyn Service>] {
+        &self.services
+    }
+
+    pub fn get(&self, name: &str) -> Result<&dyn Service, String> {
+        self.services.iter()
+            .find(|service| service.name() == name)
+            .map(|service| service.as_ref())
+            .ok_or(format!("Unknown service [{}].", name))
+    }
+
+    pub fn list(&self) -> Vec<ServiceInfo> {
+        self.services.iter().map(|service| service.info()).collect()
+    }
+
+    // Run one of the lifecycle actions by name, as sent by the UI or tray
+    pub fn control(&self, name: &str, action: &str) -> Result<(), String> {
+        let service = self.get(name)?;
+        match action {
+            "install" => service.install(),
+            "uninstall" => service.uninstall(),
+            "start" => service.start(),
+            "stop" => service.stop(),
+            "restart" => service.restart(),
+            "health" => service.health_check(),
+            _ => Err(format!("Unknown service action [{}].", action)),
+        }
+    }
+}
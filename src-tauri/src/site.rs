@@ -1,35 +1,212 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::env;
 
+use chrono::NaiveDateTime;
+use regex::{Captures, Regex};
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::access_log::{AccessLogEntry, AccessLogStats};
 use crate::configuration::Configuration;
-use crate::constants::user;
+use crate::constants::{user, Valet};
 use crate::manager::command::ValetCommandLine;
 use crate::manager::file_system::ValetFilesystem;
-use crate::manager::interface::Filesystem;
+use crate::manager::interface::{CommandLine, Filesystem};
+use crate::nginx_config::NginxConfig;
 use crate::paths::{Paths, PathTrait};
 use crate::php_fpm::PhpFpm;
+use crate::site_info::{SiteInfo, SiteKind, SiteQuery};
+use crate::site_secure::SiteSecure;
+use crate::stub::Stub;
 
 pub struct Site {
     config: Configuration,
     cli: ValetCommandLine,
     files: ValetFilesystem,
     fpm: PhpFpm,
+    site_secure: SiteSecure,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct ProxySite {
+    pub site: String,
+    pub url: String,
+    pub upstream: String,
+    pub secured: bool,
+}
+
+// Extra nginx directives of one site, kept in Snippets/{url}/{name}.conf
+#[derive(Serialize, Clone, Debug)]
+pub struct Snippet {
+    pub name: String,
+    pub content: String,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct LinkedSite {
+    pub site: String,
+    pub url: String,
+    pub path: String,
+    pub secured: bool,
 }
 
 impl Site {
-    pub fn new(config: Configuration, cli: ValetCommandLine, files: ValetFilesystem, fpm: PhpFpm) -> Self {
-        Site { config, cli, files, fpm }
+    pub fn new(config: Configuration, cli: ValetCommandLine, files: ValetFilesystem, fpm: PhpFpm, site_secure: SiteSecure) -> Self {
+        Site { config, cli, files, fpm, site_secure }
     }
     pub fn prune_links(&self) {
         self.files.ensure_dir_exists(Paths::sites_path(None).as_str(), &user(), 0o775).unwrap();
         self.files.remove_broken_links_at(Paths::sites_path(None).as_str()).unwrap();
     }
+    // Every served site plus proxy-only ones, filtered and sorted for the site list
+    pub fn sites(&self, query: &SiteQuery) -> Vec<SiteInfo> {
+        let secured = self.site_secure.secured();
+        let proxies = self.proxy_upstreams();
+        let linked: HashSet<String> = self.links().into_iter().map(|link| link.site).collect();
+        let mut sites: Vec<SiteInfo> = self.served_sites().into_iter()
+            .map(|(name, path)| {
+                let kind = if linked.contains(&name) { SiteKind::Linked } else { SiteKind::Parked };
+                self.site_info(&name, Some(path), kind, &secured, &proxies, query.with_size)
+            })
+            .collect();
+        for name in proxies.keys() {
+            if !sites.iter().any(|site| &site.name == name) {
+                sites.push(self.site_info(name, None, SiteKind::Proxy, &secured, &proxies, false));
+            }
+        }
+        sites.retain(|site| query.matches(site));
+        query.sort(&mut sites);
+        sites
+    }
+
+    // A single site, the disk size is only computed when asked for
+    pub fn info(&self, site: &str, with_size: bool) -> Result<SiteInfo, String> {
+        let secured = self.site_secure.secured();
+        let proxies = self.proxy_upstreams();
+        if let Some(path) = self.served_sites().remove(site) {
+            let link = Paths::sites_path(Some(site));
+            let kind = if self.files.is_link(&link) && self.files.exists(&link) { SiteKind::Linked } else { SiteKind::Parked };
+            return Ok(self.site_info(site, Some(path), kind, &secured, &proxies, with_size));
+        }
+        if proxies.contains_key(site) {
+            return Ok(self.site_info(site, None, SiteKind::Proxy, &secured, &proxies, false));
+        }
+        Err(format!("The [{}] site could not be found in Valet's site list.", site))
+    }
+
+    pub fn parked_paths(&self) -> Vec<String> {
+        self.config.paths()
+    }
+
+    // Serve every directory inside `path` as {directory}.{tld}, returns the new sites
+    pub fn park(&self, path: &str) -> Result<Vec<String>, String> {
+        let path = self.resolve_directory(path)?;
+        if path == Paths::sites_path(None) {
+            return Err("The Sites directory holds links and cannot be parked.".to_string());
+        }
+        self.config.add_path(&path, false);
+        Ok(self.parked_sites(&path))
+    }
+
+    pub fn unpark(&self, path: &str) -> Result<(), String> {
+        let path = self.resolve_directory(path).unwrap_or_else(|_| path.trim_end_matches('/').to_string());
+        if !self.config.paths().contains(&path) {
+            return Err(format!("The [{}] directory is not parked.", path));
+        }
+        self.config.remove_path(&path);
+        Ok(())
+    }
+
+    // Unpark `path` and drop the certificates and server blocks of the sites it served
+    pub fn forget(&self, path: &str) -> Result<Vec<String>, String> {
+        let sites = self.resolve_directory(path)
+            .map(|path| self.parked_sites(&path))
+            .unwrap_or_default();
+        self.unpark(path)?;
+        self.config.prune();
+        let served = self.served_sites();
+        let forgotten: Vec<String> = sites.into_iter().filter(|site| !served.contains_key(site)).collect();
+        for site in &forgotten {
+            self.remove_site_configuration(site);
+        }
+        Ok(forgotten)
+    }
+
+    // Serve `path` as {name}.{tld} through a symlink in Sites/
+    pub fn link(&self, path: &str, name: &str) -> Result<String, String> {
+        Self::validate_site_name(name)?;
+        let path = self.resolve_directory(path)?;
+        self.files.ensure_dir_exists(&Paths::sites_path(None), &user(), 0o775).map_err(|e| e.to_string())?;
+        self.files.symlink(&path, &Paths::sites_path(Some(name))).map_err(|e| e.to_string())?;
+        Ok(self.config.parse_domain(name))
+    }
+
+    pub fn unlink(&self, name: &str) -> Result<(), String> {
+        let link = Paths::sites_path(Some(name));
+        if !self.files.is_link(&link) {
+            return Err(format!("The [{}] site is not linked.", name));
+        }
+        self.files.unlink(&link).map_err(|e| e.to_string())?;
+        if !self.served_sites().contains_key(name) {
+            self.remove_site_configuration(name);
+        }
+        Ok(())
+    }
+
+    pub fn links(&self) -> Vec<LinkedSite> {
+        let secured = self.site_secure.secured();
+        let mut links: Vec<LinkedSite> = self.files.scandir(&Paths::sites_path(None)).unwrap_or_default()
+            .into_iter()
+            .filter(|site| self.files.exists(&Paths::sites_path(Some(site))))
+            .map(|site| {
+                let url = self.config.parse_domain(&site);
+                LinkedSite {
+                    path: self.files.realpath(&Paths::sites_path(Some(&site))),
+                    secured: secured.contains(&url),
+                    site,
+                    url,
+                }
+            })
+            .collect();
+        links.sort_by(|a, b| a.site.cmp(&b.site));
+        links
+    }
+
+    // A project directory disappeared; drop its server block unless another path still serves the name.
+    // The certificate is kept so the block can be regenerated if the directory comes back.
+    pub fn site_vanished(&self, site: &str) -> bool {
+        if self.served_sites().contains_key(site) {
+            return false;
+        }
+        let path = Paths::nginx_path(Some(&self.config.parse_domain(site)));
+        if !self.files.exists(&path) {
+            return false;
+        }
+        self.files.unlink(&path).unwrap();
+        true
+    }
+
+    // A project directory (re)appeared; rebuild the server block of a site that still has a certificate
+    pub fn site_appeared(&self, site: &str) -> bool {
+        let url = self.config.parse_domain(site);
+        if self.files.exists(&Paths::nginx_path(Some(&url))) || !self.site_secure.secured().contains(&url) {
+            return false;
+        }
+        self.site_secure.secure(&url, None).is_ok()
+    }
+
+    fn remove_site_configuration(&self, site: &str) {
+        let url = self.config.parse_domain(site);
+        self.site_secure.unsecure(&url, false);
+        self.files.unlink(&Paths::nginx_path(Some(&url))).unwrap();
+    }
+
     pub fn get_site_url(&self, directory: &str) -> Result<String, String> {
-        let tld = self.config.get("domain").ok_or("Domain not found")?;
+        let tld = self.config.domain();
         let directory = if directory == "." || directory == "./" {
             env::current_dir().unwrap().file_name().unwrap().to_str().unwrap().to_string()
         } else {
-            directory.replace(&format!(".{}", tld), "")
+            self.config.strip_domain(directory).to_string()
         };
         let served_sites = self.served_sites();
         if !served_sites.contains_key(&directory) {
@@ -38,6 +215,10 @@ impl Site {
 
         Ok(format!("{}.{}", directory, tld))
     }
+    pub fn site_path(&self, site: &str) -> Option<String> {
+        self.served_sites().get(site).cloned()
+    }
+
     pub fn php_rc_version(&self, site: &str) -> Option<String> {
         let served_sites = self.served_sites();
         if let Some(site_path) = served_sites.get(site) {
@@ -49,23 +230,438 @@ impl Site {
         None
     }
 
+    pub fn php_ini_overrides(&self, site: &str) -> BTreeMap<String, String> {
+        self.config.get_site(site, "php_ini")
+            .map(|overrides| PhpFpm::ini_map(&overrides))
+            .unwrap_or_default()
+    }
+
+    pub fn set_php_ini_override(&self, site: &str, key: &str, value: Option<&str>) -> Result<(), String> {
+        if !self.served_sites().contains_key(site) {
+            return Err(format!("The [{}] site could not be found in Valet's site list.", site));
+        }
+        let mut overrides = self.php_ini_overrides(site);
+        match value {
+            Some(value) => {
+                PhpFpm::validate_ini_setting(key, value)?;
+                overrides.insert(key.to_string(), value.to_string());
+            }
+            None => {
+                overrides.remove(key);
+            }
+        }
+        let value = if overrides.is_empty() { Value::Null } else { json!(overrides) };
+        self.config.set_site(site, "php_ini", value);
+        self.write_php_ini_overrides(site);
+        Ok(())
+    }
+
+    pub fn effective_php_ini(&self, site: &str) -> BTreeMap<String, String> {
+        let version = self.php_rc_version(site);
+        self.fpm.effective_ini(version.as_deref(), &self.php_ini_overrides(site))
+    }
+
+    // Render the site's overrides as a PHP_VALUE fastcgi param in its server block
+    fn write_php_ini_overrides(&self, site: &str) {
+        let url = self.config.parse_domain(site);
+        let path = Paths::nginx_path(Some(&url));
+        let overrides = self.php_ini_overrides(site);
+        if overrides.is_empty() && !self.files.exists(&path) {
+            return;
+        }
+        self.ensure_server_block(&url);
+
+        let contents = self.files.get(&path).unwrap();
+        let existing = Regex::new(r"(?m)^[ \t]*fastcgi_param PHP_VALUE .*\n").unwrap();
+        let mut contents = existing.replace_all(&contents, "").to_string();
+        if !overrides.is_empty() {
+            let php_value = PhpFpm::php_value(&overrides);
+            let include = Regex::new(r"(?m)^([ \t]*)include fastcgi_params;\n").unwrap();
+            contents = include.replace_all(&contents, |caps: &Captures| {
+                format!("{}{}fastcgi_param PHP_VALUE \"{}\";\n", &caps[0], &caps[1], php_value)
+            }).to_string();
+        }
+        self.files.put(&path, &contents).unwrap();
+    }
+
+    pub fn aliases(&self, site: &str) -> Vec<String> {
+        self.site_secure.aliases(&self.config.parse_domain(site))
+    }
+
+    // Extra hostnames (`acme.app` or `*.tenant`) served by the site, the tld is appended when missing
+    pub fn set_aliases(&self, site: &str, aliases: Vec<String>) -> Result<Vec<String>, String> {
+        self.ensure_known_site(site)?;
+        let hostname = Regex::new(r"^(\*\.)?[a-z0-9]([a-z0-9\-]*[a-z0-9])?(\.[a-z0-9]([a-z0-9\-]*[a-z0-9])?)*$").unwrap();
+        let url = self.config.parse_domain(site);
+        let mut normalized: Vec<String> = Vec::new();
+        for alias in aliases {
+            let alias = alias.trim().to_lowercase();
+            if alias.is_empty() {
+                continue;
+            }
+            if !hostname.is_match(&alias) {
+                return Err(format!("Invalid alias [{}].", alias));
+            }
+            let alias = self.config.parse_domain(&alias);
+            if alias != url && !normalized.contains(&alias) {
+                normalized.push(alias);
+            }
+        }
+        let value = if normalized.is_empty() { Value::Null } else { json!(normalized) };
+        self.config.set_site(site, "aliases", value);
+
+        if !self.site_secure.rewrite_server_names(&url) && !normalized.is_empty() {
+            self.ensure_server_block(&url);
+        }
+        self.site_secure.reissue_certificate(&url)?;
+        Ok(normalized)
+    }
+
+    pub fn snippets(&self, site: &str) -> Vec<Snippet> {
+        let directory = Paths::snippets_path(Some(&self.config.parse_domain(site)));
+        let mut snippets: Vec<Snippet> = self.files.scandir(&directory).unwrap_or_default()
+            .into_iter()
+            .filter_map(|file| {
+                let name = file.strip_suffix(".conf")?.to_string();
+                let content = self.files.get(&format!("{}/{}", directory, file)).ok()?;
+                Some(Snippet { name, content })
+            })
+            .collect();
+        snippets.sort_by(|a, b| a.name.cmp(&b.name));
+        snippets
+    }
+
+    // Write a snippet and keep it only when `validate` (nginx -t) accepts the result
+    pub fn save_snippet<F>(&self, site: &str, name: &str, content: &str, validate: F) -> Result<(), String>
+    where
+        F: Fn() -> Result<(), String>,
+    {
+        Self::validate_snippet_name(name)?;
+        NginxConfig::parse(content).map_err(|e| format!("Invalid snippet: {}", e))?;
+        self.ensure_known_site(site)?;
+        let url = self.config.parse_domain(site);
+        self.ensure_snippets_included(&url)?;
+
+        let directory = Paths::snippets_path(Some(&url));
+        self.files.ensure_dir_exists(&directory, &user(), 0o755).map_err(|e| e.to_string())?;
+        let path = format!("{}/{}.conf", directory, name);
+        let previous = self.files.get(&path).ok();
+        self.files.put(&path, content).map_err(|e| e.to_string())?;
+        if let Err(error) = validate() {
+            match previous {
+                Some(previous) => self.files.put(&path, &previous).map_err(|e| e.to_string())?,
+                None => self.files.unlink(&path).map_err(|e| e.to_string())?,
+            }
+            return Err(format!("nginx rejected the snippet: {}", error));
+        }
+        Ok(())
+    }
+
+    pub fn delete_snippet(&self, site: &str, name: &str) -> Result<(), String> {
+        Self::validate_snippet_name(name)?;
+        let path = format!("{}/{}.conf", Paths::snippets_path(Some(&self.config.parse_domain(site))), name);
+        if !self.files.exists(&path) {
+            return Err(format!("The [{}] site has no [{}] snippet.", site, name));
+        }
+        self.files.unlink(&path).map_err(|e| e.to_string())
+    }
+
+    pub fn domains(&self) -> Vec<String> {
+        self.config.domains()
+    }
+
+    // Serve every site on each of the TLDs, the first one has to be the current primary domain
+    pub fn set_domains(&self, domains: Vec<String>) -> Result<Vec<String>, String> {
+        let tld = Regex::new(r"^[a-z0-9]([a-z0-9\-]*[a-z0-9])?$").unwrap();
+        let mut normalized: Vec<String> = Vec::new();
+        for domain in domains {
+            let domain = domain.trim().trim_start_matches('.').to_lowercase();
+            if !tld.is_match(&domain) {
+                return Err(format!("Invalid TLD [{}].", domain));
+            }
+            if !normalized.contains(&domain) {
+                normalized.push(domain);
+            }
+        }
+        if normalized.is_empty() {
+            return Err("At least one TLD is required.".to_string());
+        }
+        let domain = self.config.domain();
+        if normalized[0] != domain {
+            return Err(format!("The primary TLD [{}] must come first, change it with set_domain.", domain));
+        }
+        self.config.set_domains(&normalized);
+        self.site_secure.update_domains(&domain)?;
+        Ok(normalized)
+    }
+
+    pub fn access_log_enabled(&self, site: &str) -> bool {
+        self.config.get_site(site, "access_log").and_then(|v| v.as_bool()).unwrap_or(false)
+    }
+
+    // Switch the site's server block between `access_log off` and a valet_json access log
+    pub fn set_access_log(&self, site: &str, enabled: bool) -> Result<(), String> {
+        if !self.served_sites().contains_key(site) {
+            return Err(format!("The [{}] site could not be found in Valet's site list.", site));
+        }
+        self.config.set_site(site, "access_log", if enabled { json!(true) } else { Value::Null });
+
+        let url = self.config.parse_domain(site);
+        let path = Paths::nginx_path(Some(&url));
+        if !enabled && !self.files.exists(&path) {
+            return Ok(());
+        }
+        self.ensure_server_block(&url);
+        let directive = if enabled {
+            format!("access_log \"{}\" valet_json;", Self::access_log_path(&url))
+        } else {
+            "access_log off;".to_string()
+        };
+        // Only server level directives, the favicon/robots locations stay silent
+        let server_access_log = Regex::new(r"(?m)^([ \t]*)access_log [^;]*;[ \t]*$").unwrap();
+        let contents = server_access_log.replace_all(&self.files.get(&path).unwrap(), |caps: &Captures| {
+            format!("{}{}", &caps[1], directive)
+        }).to_string();
+        self.files.put(&path, &contents).unwrap();
+        Ok(())
+    }
+
+    // Latest requests first
+    pub fn requests(&self, site: &str, limit: usize) -> Result<Vec<AccessLogEntry>, String> {
+        let path = Self::access_log_path(&self.config.parse_domain(site));
+        if !self.files.exists(&path) {
+            return Ok(vec![]);
+        }
+        let contents = self.files.get(&path).map_err(|e| e.to_string())?;
+        let mut entries = AccessLogEntry::parse_log(&contents);
+        entries.reverse();
+        entries.truncate(limit);
+        Ok(entries)
+    }
+
+    pub fn request_stats(&self, site: &str, slowest: usize) -> Result<AccessLogStats, String> {
+        let path = Self::access_log_path(&self.config.parse_domain(site));
+        if !self.files.exists(&path) {
+            return Ok(AccessLogStats::default());
+        }
+        let contents = self.files.get(&path).map_err(|e| e.to_string())?;
+        Ok(AccessLogStats::from_entries(&AccessLogEntry::parse_log(&contents), slowest))
+    }
+
+    // Front a local dev server (Vite, Next.js, ...) with `{site}.{tld}`
+    pub fn proxy(&self, site: &str, upstream: &str, secure: bool) -> Result<String, String> {
+        if !Regex::new(r"^[A-Za-z0-9][A-Za-z0-9.\-]*$").unwrap().is_match(site) {
+            return Err(format!("Invalid site name [{}].", site));
+        }
+        let upstream = Self::normalize_upstream(upstream)?;
+        let url = self.config.parse_domain(site);
+        self.site_secure.proxy(&url, &upstream, secure)?;
+        Ok(url)
+    }
+
+    pub fn unproxy(&self, site: &str) -> Result<(), String> {
+        let url = self.config.parse_domain(site);
+        if !self.proxies().iter().any(|proxy| proxy.url == url) {
+            return Err(format!("The [{}] site is not a proxy.", url));
+        }
+        self.site_secure.unsecure(&url, false);
+        let path = Paths::nginx_path(Some(&url));
+        if self.files.exists(&path) {
+            self.files.unlink(&path).map_err(|e| e.to_string())?;
+        }
+        Ok(())
+    }
+
+    pub fn proxies(&self) -> Vec<ProxySite> {
+        let secured = self.site_secure.secured();
+        let mut proxies: Vec<ProxySite> = self.files.scandir(&Paths::nginx_path(None)).unwrap_or_default()
+            .into_iter()
+            .filter_map(|url| {
+                let contents = self.files.get(&Paths::nginx_path(Some(&url))).ok()?;
+                let config = NginxConfig::parse(&contents).ok()?;
+                if !config.comments.iter().any(|comment| comment.ends_with("proxy.valet.conf")) {
+                    return None;
+                }
+                let upstream = config.find("proxy_pass").first()?.args.first()?.value.clone();
+                Some(ProxySite {
+                    site: self.config.strip_domain(&url).to_string(),
+                    secured: secured.contains(&url),
+                    url,
+                    upstream,
+                })
+            })
+            .collect();
+        proxies.sort_by(|a, b| a.url.cmp(&b.url));
+        proxies
+    }
+
+    // Accept `3000`, `localhost:3000` or a full http(s) URL
+    fn normalize_upstream(upstream: &str) -> Result<String, String> {
+        let upstream = upstream.trim().trim_end_matches('/');
+        let upstream = if upstream.chars().all(|c| c.is_ascii_digit()) && !upstream.is_empty() {
+            format!("http://127.0.0.1:{}", upstream)
+        } else if upstream.starts_with("http://") || upstream.starts_with("https://") {
+            upstream.to_string()
+        } else {
+            format!("http://{}", upstream)
+        };
+        if !Regex::new(r"^https?://[A-Za-z0-9.\-\[\]:]+(:\d{1,5})?$").unwrap().is_match(&upstream) {
+            return Err(format!("Invalid proxy upstream [{}].", upstream));
+        }
+        Ok(upstream)
+    }
+
+    pub fn access_log_path(url: &str) -> String {
+        format!("{}/Log/{}-access.log", Valet::home_path(), url)
+    }
+
+    // Sites served by the default valet.conf have no server block of their own yet
+    fn ensure_known_site(&self, site: &str) -> Result<(), String> {
+        if !self.served_sites().contains_key(site) && !self.proxies().iter().any(|proxy| proxy.site == site) {
+            return Err(format!("The [{}] site could not be found in Valet's site list.", site));
+        }
+        Ok(())
+    }
+
+    // Server blocks generated before snippets existed get the include after their server_name
+    fn ensure_snippets_included(&self, url: &str) -> Result<(), String> {
+        self.ensure_server_block(url);
+        let path = Paths::nginx_path(Some(url));
+        let mut contents = self.files.get(&path).map_err(|e| e.to_string())?;
+        let include = format!("include \"{}/*.conf\";", Paths::snippets_path(Some(url)));
+        if contents.contains(&include) {
+            return Ok(());
+        }
+        let config = NginxConfig::parse(&contents)?;
+        // Redirect-only blocks (http to https) never serve the site
+        let mut positions: Vec<usize> = config.directives.iter()
+            .filter(|directive| directive.name == "server" && !directive.children.iter().any(|child| child.name == "return"))
+            .filter_map(|server| server.children.iter().find(|child| child.name == "server_name")?.args.last().map(|arg| arg.end))
+            .filter_map(|end| contents[end..].find(';').map(|offset| end + offset + 1))
+            .collect();
+        positions.sort_unstable_by(|a, b| b.cmp(a));
+        for position in positions {
+            contents.insert_str(position, &format!("\n    {}", include));
+        }
+        self.files.put(&path, &contents).map_err(|e| e.to_string())
+    }
+
+    fn ensure_server_block(&self, url: &str) {
+        let path = Paths::nginx_path(Some(url));
+        if self.files.exists(&path) {
+            return;
+        }
+        let stub = Stub::load("site.valet.conf").unwrap()
+            .set("FPM_SOCKET_FILE", &self.fpm.fpm_socket_file(&self.fpm.get_current_version()));
+        let contents = self.site_secure.site_stub(url, stub).render().unwrap();
+        self.files.put(&path, &contents).unwrap();
+    }
+
     fn served_sites(&self) -> HashMap<String, String> {
         let mut parked_sites = HashMap::new();
-        let values = self.config.get("paths").unwrap();
-        let parked_paths: Vec<String> = values.to_string().split(",").map(String::from).collect();
-        for path in parked_paths {
+        for path in self.config.paths() {
             if path == Paths::sites_path(None) {
                 continue;
             }
-            for site in self.files.scandir(&path).unwrap() {
-                if self.files.is_dir(&format!("{}/{}", path, site)) {
-                    parked_sites.insert(site.clone(), format!("{}/{}", path, site));
-                }
+            for site in self.parked_sites(&path) {
+                parked_sites.insert(site.clone(), format!("{}/{}", path, site));
             }
         }
-        for linked_site in self.files.scandir(&Paths::sites_path(None)).unwrap() {
-            parked_sites.insert(linked_site.clone(), self.files.realpath(&Paths::sites_path(Some(linked_site.as_str()))));
+        for link in self.links() {
+            parked_sites.insert(link.site, link.path);
         }
         parked_sites
     }
+
+    fn proxy_upstreams(&self) -> HashMap<String, String> {
+        self.proxies().into_iter()
+            .map(|proxy| (proxy.site, proxy.upstream))
+            .collect()
+    }
+
+    fn site_info(
+        &self,
+        name: &str,
+        path: Option<String>,
+        kind: SiteKind,
+        secured: &HashSet<String>,
+        proxies: &HashMap<String, String>,
+        with_size: bool,
+    ) -> SiteInfo {
+        let url = self.config.parse_domain(name);
+        let is_secured = secured.contains(&url);
+        let scheme = if is_secured { "https" } else { "http" };
+        let isolated_php_version = self.files.get(&Paths::nginx_path(Some(&url))).ok()
+            .and_then(|contents| NginxConfig::parse(&contents).ok())
+            .and_then(|config| config.comments.iter()
+                .find_map(|comment| comment.strip_prefix("ISOLATED_PHP_VERSION=").map(|v| v.to_string())));
+        SiteInfo {
+            name: name.to_string(),
+            urls: [url.clone()].into_iter()
+                .chain(self.site_secure.aliases(&url).into_iter().filter(|alias| !alias.starts_with("*.")))
+                .map(|host| format!("{}://{}", scheme, host))
+                .collect(),
+            secured: is_secured,
+            certificate_expires_at: if is_secured { self.certificate_expiry(&url) } else { None },
+            isolated_php_version,
+            framework: path.as_deref().and_then(SiteInfo::detect_framework),
+            proxy: proxies.get(name).cloned(),
+            git_branch: path.as_deref().and_then(SiteInfo::git_branch),
+            size: if with_size { path.as_deref().and_then(|path| self.disk_size(path)) } else { None },
+            url,
+            path,
+            kind,
+        }
+    }
+
+    fn certificate_expiry(&self, url: &str) -> Option<String> {
+        let certificate = Paths::certificates_path(Some(&format!("{}.crt", url)));
+        let output = self.cli.run(&format!("openssl x509 -enddate -noout -in \"{}\"", certificate)).ok()?;
+        // notAfter=Jun  1 12:00:00 2025 GMT
+        let date = output.trim().strip_prefix("notAfter=")?.trim_end_matches(" GMT");
+        NaiveDateTime::parse_from_str(date, "%b %e %H:%M:%S %Y")
+            .ok()
+            .map(|date| date.and_utc().to_rfc3339())
+    }
+
+    fn disk_size(&self, path: &str) -> Option<u64> {
+        let output = self.cli.run(&format!("du -sb \"{}\"", path)).ok()?;
+        output.split_whitespace().next()?.parse().ok()
+    }
+
+    fn parked_sites(&self, path: &str) -> Vec<String> {
+        let mut sites: Vec<String> = self.files.scandir(path).unwrap_or_default()
+            .into_iter()
+            .filter(|site| !site.starts_with('.') && self.files.is_dir(&format!("{}/{}", path, site)))
+            .collect();
+        sites.sort();
+        sites
+    }
+
+    // Canonical path of an existing directory, `.` being the current one
+    fn resolve_directory(&self, path: &str) -> Result<String, String> {
+        let path = if path == "." || path == "./" {
+            env::current_dir().map_err(|e| e.to_string())?.to_string_lossy().to_string()
+        } else {
+            path.to_string()
+        };
+        if !self.files.is_dir(&path) {
+            return Err(format!("The [{}] directory does not exist.", path));
+        }
+        Ok(self.files.realpath(&path))
+    }
+
+    fn validate_site_name(name: &str) -> Result<(), String> {
+        if !Regex::new(r"^[A-Za-z0-9][A-Za-z0-9.\-_]*$").unwrap().is_match(name) {
+            return Err(format!("Invalid site name [{}].", name));
+        }
+        Ok(())
+    }
+
+    fn validate_snippet_name(name: &str) -> Result<(), String> {
+        if !Regex::new(r"^[A-Za-z0-9_\-]+$").unwrap().is_match(name) {
+            return Err(format!("Invalid snippet name [{}].", name));
+        }
+        Ok(())
+    }
 }
@@ -0,0 +1,137 @@
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum SiteKind {
+    Parked,
+    Linked,
+    Proxy,
+}
+
+// Everything the site list of the UI shows about one site
+#[derive(Serialize, Clone, Debug)]
+pub struct SiteInfo {
+    pub name: String,
+    pub url: String,
+    pub urls: Vec<String>,
+    pub path: Option<String>,
+    pub kind: SiteKind,
+    pub secured: bool,
+    pub certificate_expires_at: Option<String>,
+    pub isolated_php_version: Option<String>,
+    pub framework: Option<String>,
+    pub proxy: Option<String>,
+    pub git_branch: Option<String>,
+    // Bytes, only computed when asked for since it walks the whole project
+    pub size: Option<u64>,
+}
+
+#[derive(Deserialize, Clone, Copy, PartialEq, Debug, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum SiteSort {
+    #[default]
+    Name,
+    Kind,
+    Framework,
+    Secured,
+    Size,
+}
+
+#[derive(Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct SiteQuery {
+    // Matched against name, url and path
+    pub search: Option<String>,
+    pub kind: Option<SiteKind>,
+    pub secured: Option<bool>,
+    pub framework: Option<String>,
+    pub sort: SiteSort,
+    pub descending: bool,
+    pub with_size: bool,
+}
+
+impl SiteQuery {
+    pub fn matches(&self, site: &SiteInfo) -> bool {
+        if let Some(search) = self.search.as_ref().map(|s| s.to_lowercase()).filter(|s| !s.is_empty()) {
+            let haystack = format!("{} {} {}", site.name, site.url, site.path.as_deref().unwrap_or_default()).to_lowercase();
+            if !haystack.contains(&search) {
+                return false;
+            }
+        }
+        if self.kind.is_some_and(|kind| kind != site.kind) {
+            return false;
+        }
+        if self.secured.is_some_and(|secured| secured != site.secured) {
+            return false;
+        }
+        if let Some(framework) = &self.framework {
+            if !site.framework.as_ref().is_some_and(|f| f.eq_ignore_ascii_case(framework)) {
+                return false;
+            }
+        }
+        true
+    }
+
+    pub fn sort(&self, sites: &mut [SiteInfo]) {
+        sites.sort_by(|a, b| {
+            let order = match self.sort {
+                SiteSort::Name => a.name.cmp(&b.name),
+                SiteSort::Kind => format!("{:?}", a.kind).cmp(&format!("{:?}", b.kind)),
+                SiteSort::Framework => a.framework.cmp(&b.framework),
+                SiteSort::Secured => a.secured.cmp(&b.secured),
+                SiteSort::Size => a.size.cmp(&b.size),
+            };
+            order.then_with(|| a.name.cmp(&b.name))
+        });
+        if self.descending {
+            sites.reverse();
+        }
+    }
+}
+
+impl SiteInfo {
+    // Best guess of the framework from marker files, in the spirit of valet drivers
+    pub fn detect_framework(path: &str) -> Option<String> {
+        let has = |file: &str| Path::new(path).join(file).exists();
+        let framework = if has("artisan") && has("bootstrap/app.php") {
+            "Laravel"
+        } else if has("please") && has("content") {
+            "Statamic"
+        } else if has("craft") {
+            "Craft CMS"
+        } else if has("bin/console") && (has("symfony.lock") || has("config/bundles.php")) {
+            "Symfony"
+        } else if has("wp-config.php") || has("wp-load.php") || has("public/wp-load.php") {
+            "WordPress"
+        } else if has("bin/magento") {
+            "Magento"
+        } else if has("core/lib/Drupal.php") || has("web/core/lib/Drupal.php") {
+            "Drupal"
+        } else if has("next.config.js") || has("next.config.mjs") || has("next.config.ts") {
+            "Next.js"
+        } else if has("nuxt.config.js") || has("nuxt.config.ts") {
+            "Nuxt"
+        } else if has("vite.config.js") || has("vite.config.ts") {
+            "Vite"
+        } else if has("index.php") || has("public/index.php") {
+            "PHP"
+        } else if has("index.html") || has("public/index.html") {
+            "Static"
+        } else {
+            return None;
+        };
+        Some(framework.to_string())
+    }
+
+    // Current branch from .git/HEAD, or the short commit when detached
+    pub fn git_branch(path: &str) -> Option<String> {
+        let head = std::fs::read_to_string(Path::new(path).join(".git/HEAD")).ok()?;
+        let head = head.trim();
+        match head.strip_prefix("ref: refs/heads/") {
+            Some(branch) => Some(branch.to_string()),
+            None => Some(head.chars().take(7).collect()),
+        }
+    }
+}
@@ -1,9 +1,9 @@
 use std::collections::HashSet;
 use std::fs::File;
-use std::io::{Read, Write};
+use std::io::Write;
 
 use chrono::{Duration, Utc};
-use regex::Regex;
+use regex::{Captures, Regex};
 use serde_json::Value;
 
 use crate::configuration::Configuration;
@@ -13,6 +13,7 @@ use crate::manager::file_system::ValetFilesystem;
 use crate::manager::interface::{CommandLine, Filesystem};
 use crate::paths::{Paths, PathTrait};
 use crate::php_fpm::PhpFpm;
+use crate::stub::Stub;
 
 pub struct SiteSecure {
     files: ValetFilesystem,
@@ -43,23 +44,31 @@ impl SiteSecure {
         }
     }
 
-    pub fn secure(&self, url: &str, stub: Option<&str>) {
-        let binding = self.prepare_conf(url, true);
-        let stub = stub.unwrap_or(binding.as_deref().unwrap());
+    pub fn secure(&self, url: &str, stub: Option<Stub>) -> Result<(), String> {
+        let stub = match stub {
+            Some(stub) => stub,
+            None => match self.prepare_conf(url, true) {
+                Some(stub) => stub,
+                None => Stub::load("secure.valet.conf")?,
+            },
+        };
+        // Rendered before anything is touched, so a broken stub leaves the site as it was
+        let server = self.build_secure_nginx_server(url, stub)?;
         self.files.ensure_dir_exists(self.ca_path(None).as_str(), &user(), 0o775).unwrap();
         self.files.ensure_dir_exists(self.certificates_path(None).as_str(), &user(), 0o775).unwrap();
         let ca_expire_in_days = self.calculate_expiry_days(20 * 365);
         self.create_ca(ca_expire_in_days);
-        let cert_expire_in_days = self.calculate_expiry_days(1 * 365);
-        self.create_certificate(url, cert_expire_in_days);
-        self.files.put(&self.nginx_path(Some(url)), self.build_secure_nginx_server(url, stub).as_str()).unwrap();
+        let cert_expire_in_days = self.calculate_expiry_days(365);
+        self.create_certificate(url, cert_expire_in_days)?;
+        self.files.put(&self.nginx_path(Some(url)), &server).map_err(|e| e.to_string())?;
+        Ok(())
     }
 
     pub fn unsecure(&self, url: &str, preserve_unsecure_config: bool) {
         let mut stub = None;
         if self.files.exists(&self.certificates_path(Some(&(url.to_owned() + ".crt")))) {
             if preserve_unsecure_config {
-                stub = Some(self.prepare_conf(url, false));
+                stub = self.prepare_conf(url, false).or_else(|| Stub::load("site.valet.conf").ok());
             }
             self.files.unlink(&self.nginx_path(Some(url))).unwrap();
             self.files.unlink(&self.certificates_path(Some(&(url.to_owned() + ".conf")))).unwrap();
@@ -68,12 +77,81 @@ impl SiteSecure {
             self.files.unlink(&self.certificates_path(Some(&(url.to_owned() + ".crt")))).unwrap();
         }
 
-        if let Some(stub) = stub {
-            let stub = self.build_unsecure_nginx_server(url, &stub.unwrap());
-            self.files.put(&self.nginx_path(Some(url)), &stub).unwrap();
+        if let Some(Ok(server)) = stub.map(|stub| self.build_unsecure_nginx_server(url, stub)) {
+            self.files.put(&self.nginx_path(Some(url)), &server).unwrap();
         }
     }
 
+    // Serve the url by proxying every request to the upstream
+    pub fn proxy(&self, url: &str, upstream: &str, secure: bool) -> Result<(), String> {
+        let stub = Stub::load(if secure { "secure.proxy.valet.conf" } else { "proxy.valet.conf" })?
+            .set("PROXY_HOST", upstream);
+        if secure {
+            self.secure(url, Some(stub))
+        } else {
+            let server = self.build_unsecure_nginx_server(url, stub)?;
+            self.unsecure(url, false);
+            self.files.put(&self.nginx_path(Some(url)), &server).unwrap();
+            Ok(())
+        }
+    }
+
+    // Extra server names of the site served at `url`
+    pub fn aliases(&self, url: &str) -> Vec<String> {
+        self.config.get_site(self.config.strip_domain(url), "aliases")
+            .and_then(|aliases| serde_json::from_value(aliases).ok())
+            .unwrap_or_default()
+    }
+
+    // Issue a new certificate for a secured site, e.g. after its aliases changed
+    pub fn reissue_certificate(&self, url: &str) -> Result<(), String> {
+        if !self.files.exists(&self.certificates_path(Some(&format!("{}.crt", url)))) {
+            return Ok(());
+        }
+        let cert_expire_in_days = self.calculate_expiry_days(365);
+        self.create_certificate(url, cert_expire_in_days)
+    }
+
+    // The site served at `url` on every other configured TLD
+    pub fn other_domain_urls(&self, url: &str) -> Vec<String> {
+        let site = self.config.strip_domain(url);
+        self.config.domains().iter()
+            .map(|tld| format!("{}.{}", site, tld))
+            .filter(|other| other != url)
+            .collect()
+    }
+
+    // Point the server_name directives of an existing server block at the current TLDs and aliases
+    pub fn rewrite_server_names(&self, url: &str) -> bool {
+        let path = self.nginx_path(Some(url));
+        if !self.files.exists(&path) {
+            return false;
+        }
+        let server_name = Regex::new(r"(?m)^([ \t]*)server_name [^;]*;").unwrap();
+        let directive = format!("server_name {0} www.{0} *.{0}{1};", url, self.extra_server_names(url));
+        let contents = server_name.replace_all(&self.files.get(&path).unwrap(), |caps: &Captures| {
+            format!("{}{}", &caps[1], directive)
+        }).to_string();
+        self.files.put(&path, &contents).unwrap();
+        true
+    }
+
+    // Bring every server block and certificate in line with the TLD list after it changed
+    pub fn update_domains(&self, old_domain: &str) -> Result<(), String> {
+        let domain = self.config.domain();
+        if old_domain != domain {
+            self.re_secure_for_new_domain(old_domain, &domain)?;
+        }
+        for url in self.files.scandir(&self.nginx_path(None)).unwrap_or_default() {
+            if url.starts_with('.') || self.files.is_dir(&self.nginx_path(Some(&url))) {
+                continue;
+            }
+            self.rewrite_server_names(&url);
+            self.reissue_certificate(&url)?;
+        }
+        Ok(())
+    }
+
     pub fn secured(&self) -> HashSet<String> {
         let entries = self.files.scandir(&self.certificates_path(None)).unwrap();
         let mut secured_sites = HashSet::new();
@@ -84,30 +162,77 @@ impl SiteSecure {
         secured_sites
     }
 
-    pub fn regenerate_secured_sites_config(&self) {
+    pub fn regenerate_secured_sites_config(&self) -> Result<(), String> {
         for url in self.secured() {
-            self.files.put(&self.nginx_path(Some(&url)), &self.build_secure_nginx_server(&url, "")).unwrap();
+            let stub = match self.prepare_conf(&url, true) {
+                Some(stub) => stub,
+                None => Stub::load("secure.valet.conf")?,
+            };
+            self.files.put(&self.nginx_path(Some(&url)), &self.build_secure_nginx_server(&url, stub)?).unwrap();
         }
+        Ok(())
     }
 
-    pub fn re_secure_for_new_domain(&self, old_domain: &str, domain: &str) {
-        if !self.files.exists(&self.certificates_path(None)) {
-            return;
+    // Move every server block and certificate of {site}.{old_domain} over to {site}.{domain}
+    pub fn re_secure_for_new_domain(&self, old_domain: &str, domain: &str) -> Result<(), String> {
+        let old_suffix = format!(".{}", old_domain);
+        let secured = if self.files.exists(&self.certificates_path(None)) { self.secured() } else { HashSet::new() };
+        let mut urls: HashSet<String> = self.files.scandir(&self.nginx_path(None)).unwrap_or_default()
+            .into_iter()
+            .filter(|url| !self.files.is_dir(&self.nginx_path(Some(url))))
+            .collect();
+        urls.extend(secured.iter().cloned());
+
+        for old_url in urls {
+            let Some(site) = old_url.strip_suffix(&old_suffix) else {
+                continue;
+            };
+            let new_url = format!("{}.{}", site, domain);
+            let snippets = Paths::snippets_path(Some(&old_url));
+            if self.files.is_dir(&snippets) {
+                std::fs::rename(&snippets, Paths::snippets_path(Some(&new_url)))
+                    .map_err(|e| format!("Unable to move the snippets of {}: {}", old_url, e))?;
+            }
+            let nginx_conf = self.files.get(&self.nginx_path(Some(&old_url)))
+                .ok()
+                .map(|conf| Self::rename_site(&conf, &old_url, &new_url));
+            if secured.contains(&old_url) {
+                self.unsecure(&old_url, false);
+                // The existing server block is reused as is, it has no placeholders left to fill
+                let stub = nginx_conf.map(|conf| Stub::from_source(&new_url, &conf));
+                self.secure(&new_url, stub)?;
+            } else if let Some(conf) = nginx_conf {
+                self.files.unlink(&self.nginx_path(Some(&old_url))).map_err(|e| e.to_string())?;
+                self.files.put(&self.nginx_path(Some(&new_url)), &conf).map_err(|e| e.to_string())?;
+            }
         }
-        let secured = self.secured();
-        for old_url in secured {
-            let new_url = old_url.replace(&format!(".{}", old_domain), &format!(".{}", domain));
-            let has_conf = self.files.exists(&self.nginx_path(Some(&old_url)));
-            let mut nginx_conf = None;
-            if has_conf {
-                nginx_conf = Some(self.files.get(&self.nginx_path(Some(&old_url))).unwrap());
-                if let Some(ref mut conf) = nginx_conf {
-                    *conf = conf.replace(&old_url, &new_url);
+        Ok(())
+    }
+
+    // Point the server names, snippets, certificate and log paths of a server block at `new_url`.
+    // Other hostnames that merely contain the url (api.blog.test, blog.test.local, upstreams) are left alone.
+    fn rename_site(conf: &str, old_url: &str, new_url: &str) -> String {
+        let directive = Regex::new(r"(?m)^([ \t]*(?:server_name|include|ssl_certificate|ssl_certificate_key|access_log|error_log)\s)(.*)$").unwrap();
+        directive.replace_all(conf, |caps: &Captures| {
+            let args = &caps[2];
+            let mut renamed = String::new();
+            let mut last = 0;
+            for (start, _) in args.match_indices(old_url) {
+                let end = start + old_url.len();
+                let before = args[..start].trim_end_matches("www.").trim_end_matches("*.");
+                let after = &args[end..];
+                let whole_name = (before.is_empty() || before.ends_with([' ', '\t', '"', '/']))
+                    && (after.is_empty() || after.starts_with([' ', '\t', '"', ';', '/'])
+                        || [".crt", ".key", "-error.log", "-access.log"].iter().any(|suffix| after.starts_with(suffix)));
+                if whole_name && start >= last {
+                    renamed.push_str(&args[last..start]);
+                    renamed.push_str(new_url);
+                    last = end;
                 }
             }
-            self.unsecure(&old_url, false);
-            self.secure(&new_url, nginx_conf.as_deref());
-        }
+            renamed.push_str(&args[last..]);
+            format!("{}{}", &caps[1], renamed)
+        }).to_string()
     }
 
     fn create_ca(&self, ca_expire_in_days: i64) {
@@ -163,7 +288,7 @@ impl SiteSecure {
         )).unwrap();
     }
 
-    fn create_certificate(&self, url: &str, certificate_expire_in_days: i64) {
+    fn create_certificate(&self, url: &str, certificate_expire_in_days: i64) -> Result<(), String> {
         let ca_pem_path = self.ca_path(Some(&self.ca_certificate_pem));
         let ca_key_path = self.ca_path(Some(&self.ca_certificate_key));
         let ca_srl_path = self.ca_path(Some(&self.ca_certificate_srl));
@@ -173,63 +298,99 @@ impl SiteSecure {
         let crt_path = format!("{}/{}.crt", self.certificates_path(None), url);
         let conf_path = format!("{}/{}.conf", self.certificates_path(None), url);
 
-        self.files.unlink(&key_path).unwrap();
-        self.files.unlink(&csr_path).unwrap();
-        self.files.unlink(&crt_path).unwrap();
-
-        let mut conf = File::create(&conf_path).unwrap();
-        writeln!(conf, "[dn]").unwrap();
-        writeln!(conf, "CN={}", url).unwrap();
-        writeln!(conf, "[req]").unwrap();
-        writeln!(conf, "distinguished_name = dn").unwrap();
-        writeln!(conf, "[EXT]").unwrap();
-        writeln!(conf, "subjectAltName=DNS:{}", url).unwrap();
-        writeln!(conf, "keyUsage=digitalSignature").unwrap();
-        writeln!(conf, "extendedKeyUsage=serverAuth").unwrap();
-        writeln!(conf, "[x509_ext]").unwrap();
-        writeln!(conf, "subjectAltName=DNS:{}", url).unwrap();
+        for path in [&key_path, &csr_path, &crt_path] {
+            self.files.unlink(path).map_err(|e| e.to_string())?;
+        }
+
+        // The url and its subdomains (matching the server_name wildcard) on every TLD, and the site's aliases
+        let subject_alt_names = std::iter::once(url.to_string())
+            .chain(self.other_domain_urls(url))
+            .flat_map(|name| [format!("*.{}", name), name])
+            .chain(self.aliases(url))
+            .map(|name| format!("DNS:{}", name))
+            .collect::<Vec<String>>()
+            .join(",");
+
+        Self::write_certificate_conf(&conf_path, url, &subject_alt_names)
+            .map_err(|e| format!("Unable to write {}: {}", conf_path, e))?;
 
         self.cli.run_as_user(&format!(
             "openssl req -new -newkey rsa:2048 -sha256 -nodes -keyout \"{}\" -subj \"/CN={}\" -out \"{}\" -config \"{}\"",
             key_path, url, csr_path, conf_path
-        )).unwrap();
+        )).map_err(|e| format!("Unable to create the certificate request for {}: {}", url, e))?;
         self.cli.run_as_user(&format!(
             "openssl x509 -req -sha256 -in \"{}\" -CA \"{}\" -CAkey \"{}\" -CAcreateserial -CAserial \"{}\" -out \"{}\" -days {} -extfile \"{}\" -extensions x509_ext",
             csr_path, ca_pem_path, ca_key_path, ca_srl_path, crt_path, certificate_expire_in_days, conf_path
-        )).unwrap();
+        )).map_err(|e| format!("Unable to sign the certificate for {}: {}", url, e))?;
+        Ok(())
     }
 
-    fn build_unsecure_nginx_server(&self, url: &str, stub: &str) -> String {
+    fn write_certificate_conf(path: &str, url: &str, subject_alt_names: &str) -> std::io::Result<()> {
+        let mut conf = File::create(path)?;
+        writeln!(conf, "[dn]")?;
+        writeln!(conf, "CN={}", url)?;
+        writeln!(conf, "[req]")?;
+        writeln!(conf, "distinguished_name = dn")?;
+        writeln!(conf, "[EXT]")?;
+        writeln!(conf, "subjectAltName={}", subject_alt_names)?;
+        writeln!(conf, "keyUsage=digitalSignature")?;
+        writeln!(conf, "extendedKeyUsage=serverAuth")?;
+        writeln!(conf, "[x509_ext]")?;
+        writeln!(conf, "subjectAltName={}", subject_alt_names)?;
+        Ok(())
+    }
+
+    // Variables and flags every site stub shares, stubs built by prepare_conf keep their own socket and isolation
+    pub fn site_stub(&self, url: &str, stub: Stub) -> Stub {
         let unsecure_port = self.config.get("port").unwrap_or(Value::String("80".to_string()));
-        let secure_port = self.config.get("https_port").unwrap_or(Value::String("433".to_string()));
+        let secure_port = self.config.get("https_port").unwrap_or(Value::String("443".to_string()));
+        let loopback = self.config.get("loopback").and_then(|value| value.as_str().map(|value| value.to_string()))
+            .filter(|address| !address.is_empty() && address != "127.0.0.1");
+        let isolated = stub.has("ISOLATED_PHP_VERSION");
+        // Per-site php.ini overrides and access log, so re-rendering a server block keeps them
+        let php_ini = self.config.get_site(self.config.strip_domain(url), "php_ini")
+            .map(|overrides| PhpFpm::ini_map(&overrides))
+            .unwrap_or_default();
+        let access_log = self.config.get_site(self.config.strip_domain(url), "access_log")
+            .and_then(|value| value.as_bool())
+            .unwrap_or(false);
+        let stub = if stub.has("FPM_SOCKET_FILE") { stub } else { stub.set("FPM_SOCKET_FILE", &self.fpm_socket_file(None)) };
         stub
-            .replace("VALET_HOME_PATH", Valet::home_path().as_str())
-            .replace("VALET_SERVER_PATH", VALET_SERVER_PATH)
-            .replace("VALET_STATIC_PREFIX", VALET_STATIC_PREFIX)
-            .replace("VALET_SITE", url)
-            .replace("VALET_HTTP_PORT", unsecure_port.clone().as_str().unwrap())
-            .replace("VALET_HTTPS_PORT", secure_port.clone().as_str().unwrap())
+            .set("HOME_PATH", &Valet::home_path())
+            .set("SERVER_PATH", VALET_SERVER_PATH)
+            .set("STATIC_PREFIX", VALET_STATIC_PREFIX)
+            .set("ALIASES", self.extra_server_names(url).trim_start())
+            .set("SITE", url)
+            .set("HTTP_PORT", unsecure_port.as_str().unwrap_or("80"))
+            .set("HTTPS_PORT", secure_port.as_str().unwrap_or("443"))
+            .set("LOOPBACK", loopback.as_deref().unwrap_or("127.0.0.1"))
+            .set("PHP_VALUE", &PhpFpm::php_value(&php_ini))
+            .flag("php_ini", !php_ini.is_empty())
+            .flag("access_log", access_log)
+            .flag("loopback", loopback.is_some())
+            .flag("http2", self.config.get("http2").and_then(|value| value.as_bool()).unwrap_or(true))
+            .flag("isolated", isolated)
     }
 
-    fn build_secure_nginx_server(&self, url: &str, stub: &str) -> String {
-        let secure_port = self.config.get("https_port").unwrap_or(Value::String("433".to_string()));
-        let unsecure_port = self.config.get("port").unwrap_or(Value::String("80".to_string()));
-        let mut file = File::open(format!("{}/stubs/secure.valet.conf", Valet::root_path())).unwrap();
-        let mut contents = String::new();
+    fn build_unsecure_nginx_server(&self, url: &str, stub: Stub) -> Result<String, String> {
+        self.site_stub(url, stub).render()
+    }
+
+    fn build_secure_nginx_server(&self, url: &str, stub: Stub) -> Result<String, String> {
         let path = self.certificates_path(None);
-        file.read_to_string(&mut contents).unwrap();
-        stub
-            .replace("VALET_HOME_PATH", Valet::home_path().as_str())
-            .replace("VALET_SERVER_PATH", VALET_SERVER_PATH)
-            .replace("VALET_STATIC_PREFIX", VALET_STATIC_PREFIX)
-            .replace("VALET_SITE", url)
-            .replace("VALET_CERT", format!("{}?{}.cert", path, url).as_str())
-            .replace("VALET_KEY", format!("{}?{}.key", path, url).as_str())
-            .replace("VALET_HTTP_PORT", unsecure_port.as_str().unwrap())
-            .replace("VALET_HTTPS_PORT", secure_port.as_str().unwrap())
-            .replace("VALET_REDIRECT_PORT", self.https_suffix().as_str())
-        // todo!()
-            // .replace("VALET_FPM_SOCKET_FILE", self.fpm.socket_file_name(None).as_str())
+        self.site_stub(url, stub)
+            .set("CERT", &format!("{}/{}.crt", path, url))
+            .set("KEY", &format!("{}/{}.key", path, url))
+            .set("REDIRECT_PORT", &self.https_suffix())
+            .render()
+    }
+
+    // The other TLDs and the aliases, as they are appended to a server_name directive
+    pub fn extra_server_names(&self, url: &str) -> String {
+        self.other_domain_urls(url).iter()
+            .map(|other| format!(" {0} www.{0} *.{0}", other))
+            .chain(self.aliases(url).iter().map(|alias| format!(" {}", alias)))
+            .collect()
     }
 
     fn calculate_expiry_days(&self, days: i64) -> i64 {
@@ -250,7 +411,15 @@ impl SiteSecure {
         Paths::ca_path(file)
     }
 
-    fn prepare_conf(&self, url: &str, secure: bool) -> Option<String> {
+    // Socket of the valet pool for `version`, the configured PHP version by default
+    fn fpm_socket_file(&self, version: Option<&str>) -> String {
+        let version = version.map(|version| version.to_string())
+            .or_else(|| self.config.get("php_version").and_then(|value| value.as_str().map(|value| value.to_string())))
+            .unwrap_or_default();
+        format!("{}/{}", Valet::home_path(), PhpFpm::socket_file(&version))
+    }
+
+    fn prepare_conf(&self, url: &str, secure: bool) -> Option<Stub> {
         if !self.files.exists(&self.nginx_path(Some(url))) {
             return None;
         }
@@ -264,27 +433,15 @@ impl SiteSecure {
         let stub = stub_detail.name("stub").unwrap().as_str();
         if stub == "proxy" {
             let proxy_pass = self.get_proxy_pass(url, Some(&existing_conf)).unwrap();
-            let stub_path = if secure {
-                format!("{}/stubs/secure.proxy.valet.conf", Valet::root_path())
-            } else {
-                format!("{}/stubs/proxy.valet.conf", Valet::root_path())
-            };
-            let mut stub = self.files.get(&stub_path).unwrap();
-            stub = stub.replace("VALET_PROXY_HOST", &proxy_pass);
-            return Some(stub);
+            let stub = Stub::load(if secure { "secure.proxy.valet.conf" } else { "proxy.valet.conf" }).ok()?;
+            return Some(stub.set("PROXY_HOST", &proxy_pass));
         }
         if stub == "isolated" {
             let php_version = self.isolated_php_version(&existing_conf);
-            let stub_path = if secure {
-                format!("{}/stubs/secure.isolated.valet.conf", Valet::root_path())
-            } else {
-                format!("{}/stubs/isolated.valet.conf", Valet::root_path())
-            };
-            let mut stub = self.files.get(&stub_path).unwrap();
-            // stub = stub.replace("VALET_FPM_SOCKET_FILE", self.fpm.socket_file_name(Some(php_version.as_str())).as_str());
-            stub = stub.replace("VALET_ISOLATED_PHP_VERSION", &php_version);
-            return Some(stub);
-            todo!();
+            let stub = Stub::load(if secure { "secure.valet.conf" } else { "site.valet.conf" }).ok()?;
+            return Some(stub
+                .set("FPM_SOCKET_FILE", &self.fpm_socket_file(Some(&php_version)))
+                .set("ISOLATED_PHP_VERSION", &php_version));
         }
         None
     }
@@ -298,8 +455,7 @@ impl SiteSecure {
     }
 
     fn generate_certificate_conf(&self, path: &str, url: &str) {
-        let config = self.files.get(&format!("{}/stubs/openssl.conf", Valet::root_path())).unwrap();
-        let config = config.replace("VALET_DOMAIN", url);
+        let config = Stub::load("openssl.conf").unwrap().set("DOMAIN", url).render().unwrap();
         self.files.put(path, &config).unwrap();
     }
 
@@ -313,7 +469,7 @@ impl SiteSecure {
     }
 
     fn isolated_php_version(&self, site_conf: &str) -> String {
-        let re = Regex::new(r"^# ISOLATED_PHP_VERSION=(.*?)\n").unwrap();
+        let re = Regex::new(r"(?m)^# ISOLATED_PHP_VERSION=(.*?)\n").unwrap();
         if site_conf.contains("# ISOLATED_PHP_VERSION") {
             if let Some(captures) = re.captures(site_conf) {
                 return captures.get(1).unwrap().as_str().to_string();
@@ -323,3 +479,36 @@ impl SiteSecure {
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renames_only_the_site_itself() {
+        let conf = "server {
+    server_name blog.test www.blog.test *.blog.test api.blog.test blog.test.local;
+    include \"/home/me/.config/valetui/Snippets/blog.test/*.conf\";
+    ssl_certificate \"/home/me/.config/valetui/Certificates/blog.test.crt\";
+    ssl_certificate_key /home/me/.config/valetui/Certificates/blog.test.key;
+    access_log \"/home/me/.config/valetui/Log/blog.test-access.log\" valet_json;
+    error_log /home/me/.config/valetui/Log/blog.test-error.log;
+    location / {
+        proxy_pass http://blog.test:8080;
+    }
+}
+";
+        assert_eq!(SiteSecure::rename_site(conf, "blog.test", "blog.dev"), "server {
+    server_name blog.dev www.blog.dev *.blog.dev api.blog.test blog.test.local;
+    include \"/home/me/.config/valetui/Snippets/blog.dev/*.conf\";
+    ssl_certificate \"/home/me/.config/valetui/Certificates/blog.dev.crt\";
+    ssl_certificate_key /home/me/.config/valetui/Certificates/blog.dev.key;
+    access_log \"/home/me/.config/valetui/Log/blog.dev-access.log\" valet_json;
+    error_log /home/me/.config/valetui/Log/blog.dev-error.log;
+    location / {
+        proxy_pass http://blog.test:8080;
+    }
+}
+");
+    }
+}
@@ -0,0 +1,74 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use notify::event::{ModifyKind, RenameMode};
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use serde::Serialize;
+
+#[derive(Serialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum SiteChange {
+    Added,
+    Removed,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct SiteEvent {
+    pub change: SiteChange,
+    pub site: String,
+    pub path: String,
+}
+
+// Watches parked directories and Sites/ for project directories coming and going
+#[derive(Default)]
+pub struct SiteWatcher {
+    watcher: Mutex<Option<RecommendedWatcher>>,
+}
+
+impl SiteWatcher {
+    // Replace the watched directories, e.g. after a park or unpark
+    pub fn watch<F>(&self, directories: Vec<String>, on_event: F) -> Result<(), String>
+    where
+        F: Fn(SiteEvent) + Send + 'static,
+    {
+        let watched: HashSet<PathBuf> = directories.iter().map(PathBuf::from).collect();
+        let parents = watched.clone();
+        let mut watcher = notify::recommended_watcher(move |event: notify::Result<Event>| {
+            let Ok(event) = event else {
+                return;
+            };
+            let change = match event.kind {
+                EventKind::Create(_) | EventKind::Modify(ModifyKind::Name(RenameMode::To)) => SiteChange::Added,
+                EventKind::Remove(_) | EventKind::Modify(ModifyKind::Name(RenameMode::From)) => SiteChange::Removed,
+                _ => return,
+            };
+            for path in &event.paths {
+                if !path.parent().is_some_and(|parent| parents.contains(parent)) {
+                    continue;
+                }
+                let Some(site) = path.file_name().map(|name| name.to_string_lossy().to_string()) else {
+                    continue;
+                };
+                // Files and hidden entries are not sites
+                if site.starts_with('.') || (change == SiteChange::Added && !path.is_dir()) {
+                    continue;
+                }
+                on_event(SiteEvent { change, site, path: path.to_string_lossy().to_string() });
+            }
+        }).map_err(|e| e.to_string())?;
+
+        for directory in &watched {
+            if Path::new(directory).is_dir() {
+                watcher.watch(directory, RecursiveMode::NonRecursive)
+                    .map_err(|e| format!("Unable to watch {}: {}", directory.display(), e))?;
+            }
+        }
+        *self.watcher.lock().unwrap() = Some(watcher);
+        Ok(())
+    }
+
+    pub fn unwatch(&self) {
+        self.watcher.lock().unwrap().take();
+    }
+}
@@ -0,0 +1,193 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use include_dir::{include_dir, Dir};
+use regex::{Captures, Regex};
+
+use crate::constants::Valet;
+
+// Compiled into the binary, installed builds do not depend on the source tree
+static BUNDLED: Dir<'_> = include_dir!("$CARGO_MANIFEST_DIR/src/stubs");
+
+// A configuration template bundled from src/stubs, or the user's copy in ~/.config/valetui/stubs.
+//
+// `VALET_NAME` placeholders are filled from `set("NAME", ..)`, and
+// `{{#if flag}} .. {{else}} .. {{/if}}` keeps one branch depending on `flag("flag", ..)`.
+// Rendering fails on placeholders or flags nobody set, instead of writing them out.
+#[derive(Clone, Debug)]
+pub struct Stub {
+    name: String,
+    source: String,
+    variables: BTreeMap<String, String>,
+    flags: BTreeMap<String, bool>,
+}
+
+impl Stub {
+    // The user override when there is one, the bundled stub otherwise
+    pub fn load(name: &str) -> Result<Stub, String> {
+        if Self::is_overridden(name) {
+            let path = Self::override_path(name);
+            let source = std::fs::read_to_string(&path).map_err(|e| format!("Unable to read stub {}: {}", path, e))?;
+            return Ok(Self::from_source(name, &source));
+        }
+        let source = Self::bundled(name).ok_or(format!("Unknown stub {}.", name))?;
+        Ok(Self::from_source(name, source))
+    }
+
+    pub fn from_source(name: &str, source: &str) -> Stub {
+        Stub { name: name.to_string(), source: source.to_string(), variables: BTreeMap::new(), flags: BTreeMap::new() }
+    }
+
+    pub fn bundled(name: &str) -> Option<&'static str> {
+        BUNDLED.get_file(name)?.contents_utf8()
+    }
+
+    pub fn override_path(name: &str) -> String {
+        format!("{}/stubs/{}", Valet::home_path(), name)
+    }
+
+    pub fn is_overridden(name: &str) -> bool {
+        Path::new(&Self::override_path(name)).is_file()
+    }
+
+    pub fn set(mut self, name: &str, value: &str) -> Self {
+        self.variables.insert(name.to_string(), value.to_string());
+        self
+    }
+
+    pub fn has(&self, name: &str) -> bool {
+        self.variables.contains_key(name)
+    }
+
+    pub fn flag(mut self, name: &str, value: bool) -> Self {
+        self.flags.insert(name.to_string(), value);
+        self
+    }
+
+    pub fn render(&self) -> Result<String, String> {
+        let source = self.conditionals()?;
+        let placeholder = Regex::new(r"VALET_([A-Z0-9_]*[A-Z0-9])").unwrap();
+        let mut unknown: Vec<String> = Vec::new();
+        let rendered = placeholder.replace_all(&source, |caps: &Captures| {
+            match self.variables.get(&caps[1]) {
+                Some(value) => value.clone(),
+                None => {
+                    if !unknown.contains(&caps[0].to_string()) {
+                        unknown.push(caps[0].to_string());
+                    }
+                    caps[0].to_string()
+                }
+            }
+        }).to_string();
+        if !unknown.is_empty() {
+            return Err(format!("Unknown placeholder {} in stub {}.", unknown.join(", "), self.name));
+        }
+        Ok(rendered)
+    }
+
+    // Resolve {{#if}} blocks, tags alone on their line take the line with them
+    fn conditionals(&self) -> Result<String, String> {
+        let tag = Regex::new(r"(?m)^[ \t]*\{\{(#if [a-z0-9_]+|else|/if)\}\}[ \t]*\n|\{\{(#if [a-z0-9_]+|else|/if)\}\}").unwrap();
+        let mut output = String::new();
+        // (branch taken, inside the else branch) of every open block
+        let mut stack: Vec<(bool, bool)> = Vec::new();
+        let mut position = 0;
+        for caps in tag.captures_iter(&self.source) {
+            let whole = caps.get(0).unwrap();
+            if stack.iter().all(|(taken, in_else)| taken != in_else) {
+                output.push_str(&self.source[position..whole.start()]);
+            }
+            position = whole.end();
+            let tag = caps.get(1).or(caps.get(2)).unwrap().as_str();
+            match tag {
+                "else" => match stack.last_mut() {
+                    Some((_, in_else @ false)) => *in_else = true,
+                    _ => return Err(format!("Unexpected {{{{else}}}} in stub {}.", self.name)),
+                },
+                "/if" => {
+                    stack.pop().ok_or(format!("Unexpected {{{{/if}}}} in stub {}.", self.name))?;
+                }
+                _ => {
+                    let flag = &tag["#if ".len()..];
+                    let value = *self.flags.get(flag).ok_or(format!("Unknown condition [{}] in stub {}.", flag, self.name))?;
+                    stack.push((value, false));
+                }
+            }
+        }
+        if !stack.is_empty() {
+            return Err(format!("Unclosed {{{{#if}}}} in stub {}.", self.name));
+        }
+        output.push_str(&self.source[position..]);
+        Ok(output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_stub_in_use_is_bundled() {
+        let names = [
+            "SampleValetDriver.php", "composer.shim", "dnsmasq.conf", "dnsmasq_options", "fastcgi_params", "fpm.conf",
+            "init/mailpit", "init/mailpit.sh", "networkmanager.conf", "nginx.conf", "openssl.conf", "php.shim",
+            "proxy.valet.conf", "secure.proxy.valet.conf", "secure.valet.conf", "share.valet.conf", "site.valet.conf",
+            "valet.conf",
+        ];
+        for name in names {
+            assert!(Stub::bundled(name).is_some(), "{} is not bundled", name);
+        }
+        assert!(Stub::bundled("missing.conf").is_none());
+    }
+
+    #[test]
+    fn fills_named_placeholders() {
+        let stub = Stub::from_source("site", "server_name VALET_SITE;\nerror_log VALET_HOME_PATH/Log/VALET_SITE-error.log;\n")
+            .set("SITE", "blog.test")
+            .set("HOME_PATH", "/home/me/.config/valetui");
+        assert_eq!(
+            stub.render().unwrap(),
+            "server_name blog.test;\nerror_log /home/me/.config/valetui/Log/blog.test-error.log;\n",
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_placeholders() {
+        let error = Stub::from_source("site", "listen VALET_HTTP_PROT;\nroot VALET_SITE;").set("SITE", "blog.test").render().unwrap_err();
+        assert_eq!(error, "Unknown placeholder VALET_HTTP_PROT in stub site.");
+    }
+
+    #[test]
+    fn keeps_the_branch_of_the_flag() {
+        let source = "server {\n    {{#if http2}}\n    listen 443 ssl http2;\n    {{else}}\n    listen 443 ssl;\n    {{/if}}\n    root /;\n}\n";
+        let with = Stub::from_source("secure", source).flag("http2", true).render().unwrap();
+        assert_eq!(with, "server {\n    listen 443 ssl http2;\n    root /;\n}\n");
+        let without = Stub::from_source("secure", source).flag("http2", false).render().unwrap();
+        assert_eq!(without, "server {\n    listen 443 ssl;\n    root /;\n}\n");
+    }
+
+    #[test]
+    fn supports_inline_and_nested_conditions() {
+        let source = "listen 443 ssl{{#if http2}} http2{{/if}};\n{{#if secure}}\n{{#if loopback}}\nlisten VALET_LOOPBACK:443;\n{{/if}}\n{{/if}}\n";
+        let stub = Stub::from_source("proxy", source).flag("http2", false).flag("secure", true).flag("loopback", false);
+        assert_eq!(stub.render().unwrap(), "listen 443 ssl;\n");
+        let stub = stub.flag("loopback", true).set("LOOPBACK", "127.0.0.2");
+        assert_eq!(stub.render().unwrap(), "listen 443 ssl;\nlisten 127.0.0.2:443;\n");
+    }
+
+    #[test]
+    fn placeholders_of_skipped_branches_are_not_required() {
+        let source = "{{#if status}}pm.status_path = VALET_FPM_STATUS_PATH{{else}};pm.status_path ={{/if}}\n";
+        assert_eq!(Stub::from_source("fpm", source).flag("status", false).render().unwrap(), ";pm.status_path =\n");
+    }
+
+    #[test]
+    fn rejects_unknown_and_unbalanced_conditions() {
+        assert_eq!(
+            Stub::from_source("site", "{{#if tls}}x{{/if}}").render().unwrap_err(),
+            "Unknown condition [tls] in stub site.",
+        );
+        assert!(Stub::from_source("site", "{{#if tls}}x").flag("tls", true).render().is_err());
+        assert!(Stub::from_source("site", "x{{/if}}").render().is_err());
+    }
+}
@@ -16,6 +16,7 @@ PATH=/usr/local/sbin:/usr/local/bin:/sbin:/bin:/usr/sbin:/usr/bin
 PIDFILE=/opt/valet-linux/mailpit.pid
 LOGFILE=/opt/valet-linux/mailpit.log
 DAEMON=/usr/local/bin/mailpit
+DAEMON_OPTS="--smtp 127.0.0.1:1025 --listen 127.0.0.1:8025"
 NAME=mailpit
 DESC="Mailpit Service"
 
@@ -27,8 +28,10 @@ test -x $DAEMON || exit 0
 . /lib/lsb/init-functions
 
 start() {
+    mkdir -p "$(dirname "$PIDFILE")"
     test -f "$PIDFILE" && rm "$PIDFILE"
-    start-stop-daemon --start --pidfile $PIDFILE --exec $DAEMON start || return 2
+    start-stop-daemon --start --background --make-pidfile --pidfile $PIDFILE \
+        --startas /bin/sh -- -c "exec $DAEMON $DAEMON_OPTS >> $LOGFILE 2>&1" || return 2
 }
 
 stop() {
@@ -51,7 +54,7 @@ case "$1" in
         start
     ;;
     status)
-        exec $DAEMON status
+        status_of_proc -p $PIDFILE $DAEMON $NAME && exit 0 || exit $?
     ;;
     *)
         echo "Usage: $0 {start|stop|restart|status}"
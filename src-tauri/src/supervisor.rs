@@ -0,0 +1,352 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::os::unix::process::CommandExt;
+use std::path::Path;
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use chrono::Local;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::constants::Valet;
+
+fn enabled() -> bool {
+    true
+}
+
+// A long running process a site declares in `.valetui.toml` or its Procfile
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ProcessDefinition {
+    #[serde(default)]
+    pub name: String,
+    pub command: String,
+    // Relative to the site directory
+    #[serde(default)]
+    pub cwd: Option<String>,
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    #[serde(default = "enabled")]
+    pub autostart: bool,
+    #[serde(default = "enabled")]
+    pub autorestart: bool,
+}
+
+// [processes.queue]
+// command = "php artisan queue:work"
+#[derive(Deserialize, Default)]
+struct SiteManifest {
+    #[serde(default)]
+    processes: BTreeMap<String, ProcessDefinition>,
+}
+
+#[derive(Serialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum ProcessState {
+    Running,
+    Backoff,
+    Exited,
+    Crashed,
+    Stopped,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct ProcessStatus {
+    pub site: String,
+    pub name: String,
+    pub command: String,
+    pub state: ProcessState,
+    pub pid: Option<u32>,
+    pub restarts: u32,
+    pub exit_code: Option<i32>,
+    pub started_at: Option<String>,
+    pub log_file: String,
+}
+
+struct Supervised {
+    status: Arc<Mutex<ProcessStatus>>,
+    stop: Arc<AtomicBool>,
+    monitor: JoinHandle<()>,
+}
+
+impl ProcessDefinition {
+    pub const MANIFEST: &'static str = ".valetui.toml";
+    pub const PROCFILE: &'static str = "Procfile";
+
+    // `.valetui.toml` wins over a Procfile when a site has both
+    pub fn load(site_path: &str) -> Result<Vec<ProcessDefinition>, String> {
+        let manifest = format!("{}/{}", site_path, Self::MANIFEST);
+        let procfile = format!("{}/{}", site_path, Self::PROCFILE);
+        let definitions = if Path::new(&manifest).exists() {
+            let contents = std::fs::read_to_string(&manifest).map_err(|e| e.to_string())?;
+            let manifest: SiteManifest = toml::from_str(&contents)
+                .map_err(|e| format!("Invalid {}: {}", Self::MANIFEST, e))?;
+            manifest.processes.into_iter()
+                .map(|(name, definition)| ProcessDefinition { name, ..definition })
+                .collect()
+        } else if Path::new(&procfile).exists() {
+            Self::parse_procfile(&std::fs::read_to_string(&procfile).map_err(|e| e.to_string())?)
+        } else {
+            vec![]
+        };
+        for definition in &definitions {
+            Self::validate_name(&definition.name)?;
+        }
+        Ok(definitions)
+    }
+
+    // web: npm run dev
+    pub fn parse_procfile(contents: &str) -> Vec<ProcessDefinition> {
+        let line = Regex::new(r"^([A-Za-z0-9_\-]+):\s*(.+)$").unwrap();
+        contents.lines()
+            .filter_map(|l| line.captures(l.trim()))
+            .map(|caps| ProcessDefinition {
+                name: caps[1].to_string(),
+                command: caps[2].trim().to_string(),
+                cwd: None,
+                env: BTreeMap::new(),
+                autostart: true,
+                autorestart: true,
+            })
+            .collect()
+    }
+
+    fn validate_name(name: &str) -> Result<(), String> {
+        if !Regex::new(r"^[A-Za-z0-9_\-]+$").unwrap().is_match(name) {
+            return Err(format!("Invalid process name [{}].", name));
+        }
+        Ok(())
+    }
+}
+
+// Runs site processes, restarts them with a backoff when they crash and keeps their output in Log/
+#[derive(Default)]
+pub struct Supervisor {
+    processes: Mutex<HashMap<String, Supervised>>,
+}
+
+impl Supervisor {
+    const MAX_BACKOFF: Duration = Duration::from_secs(30);
+    // A process that ran this long is considered healthy again
+    const HEALTHY_AFTER: Duration = Duration::from_secs(60);
+    const STOP_TIMEOUT: Duration = Duration::from_secs(5);
+
+    pub fn log_file(site: &str, name: &str) -> String {
+        format!("{}/Log/{}.{}.log", Valet::home_path(), site, name)
+    }
+
+    // Start every autostart process of the site
+    pub fn start_site(&self, site: &str, site_path: &str) -> Result<(), String> {
+        for definition in ProcessDefinition::load(site_path)? {
+            if definition.autostart {
+                self.start(site, site_path, definition);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn start_process(&self, site: &str, site_path: &str, name: &str) -> Result<(), String> {
+        let definition = ProcessDefinition::load(site_path)?
+            .into_iter()
+            .find(|definition| definition.name == name)
+            .ok_or(format!("The [{}] site does not declare a [{}] process.", site, name))?;
+        self.start(site, site_path, definition);
+        Ok(())
+    }
+
+    pub fn start(&self, site: &str, site_path: &str, definition: ProcessDefinition) {
+        let key = Self::key(site, &definition.name);
+        let mut processes = self.processes.lock().unwrap();
+        if let Some(process) = processes.get(&key) {
+            if matches!(process.status.lock().unwrap().state, ProcessState::Running | ProcessState::Backoff) {
+                return;
+            }
+            process.stop.store(true, Ordering::SeqCst);
+        }
+
+        let status = Arc::new(Mutex::new(ProcessStatus {
+            site: site.to_string(),
+            name: definition.name.clone(),
+            command: definition.command.clone(),
+            state: ProcessState::Backoff,
+            pid: None,
+            restarts: 0,
+            exit_code: None,
+            started_at: None,
+            log_file: Self::log_file(site, &definition.name),
+        }));
+        let stop = Arc::new(AtomicBool::new(false));
+        let cwd = match &definition.cwd {
+            Some(cwd) => format!("{}/{}", site_path, cwd),
+            None => site_path.to_string(),
+        };
+        let monitor = {
+            let status = status.clone();
+            let stop = stop.clone();
+            thread::spawn(move || Self::monitor(definition, cwd, status, stop))
+        };
+        processes.insert(key, Supervised { status, stop, monitor });
+    }
+
+    pub fn stop(&self, site: &str, name: &str) {
+        if let Some(process) = self.processes.lock().unwrap().remove(&Self::key(site, name)) {
+            process.stop.store(true, Ordering::SeqCst);
+        }
+    }
+
+    // Called when a site is unlinked or its directory vanished
+    pub fn stop_site(&self, site: &str) {
+        let prefix = format!("{}/", site);
+        self.processes.lock().unwrap().retain(|key, process| {
+            if key.starts_with(&prefix) {
+                process.stop.store(true, Ordering::SeqCst);
+                return false;
+            }
+            true
+        });
+    }
+
+    // Stop everything and wait for it, used when valetui exits
+    pub fn stop_all(&self) {
+        let processes: Vec<Supervised> = self.processes.lock().unwrap().drain().map(|(_, process)| process).collect();
+        for process in &processes {
+            process.stop.store(true, Ordering::SeqCst);
+        }
+        for process in processes {
+            let _ = process.monitor.join();
+        }
+    }
+
+    pub fn restart(&self, site: &str, site_path: &str, name: &str) -> Result<(), String> {
+        let previous = self.processes.lock().unwrap().remove(&Self::key(site, name));
+        if let Some(process) = previous {
+            process.stop.store(true, Ordering::SeqCst);
+            // The old process group is gone once its monitor returns, so two copies never overlap
+            let _ = process.monitor.join();
+        }
+        self.start_process(site, site_path, name)
+    }
+
+    pub fn processes(&self, site: Option<&str>) -> Vec<ProcessStatus> {
+        let mut processes: Vec<ProcessStatus> = self.processes.lock().unwrap()
+            .values()
+            .map(|process| process.status.lock().unwrap().clone())
+            .filter(|status| site.is_none_or(|site| status.site == site))
+            .collect();
+        processes.sort_by(|a, b| (&a.site, &a.name).cmp(&(&b.site, &b.name)));
+        processes
+    }
+
+    fn key(site: &str, name: &str) -> String {
+        format!("{}/{}", site, name)
+    }
+
+    fn monitor(definition: ProcessDefinition, cwd: String, status: Arc<Mutex<ProcessStatus>>, stop: Arc<AtomicBool>) {
+        let log_file = status.lock().unwrap().log_file.clone();
+        let mut backoff = Duration::from_secs(1);
+        loop {
+            if stop.load(Ordering::SeqCst) {
+                break;
+            }
+            let started = Instant::now();
+            let exit_code = match Self::spawn(&definition, &cwd, &log_file) {
+                Ok(mut child) => {
+                    {
+                        let mut status = status.lock().unwrap();
+                        status.state = ProcessState::Running;
+                        status.pid = Some(child.id());
+                        status.started_at = Some(Local::now().to_rfc3339());
+                    }
+                    match Self::wait(&mut child, &stop) {
+                        Some(code) => code,
+                        None => break,
+                    }
+                }
+                Err(error) => {
+                    Self::log(&log_file, &format!("unable to start: {}", error));
+                    None
+                }
+            };
+            Self::log(&log_file, &format!("exited with {}", exit_code.map_or("signal".to_string(), |c| c.to_string())));
+
+            let mut current = status.lock().unwrap();
+            current.pid = None;
+            current.exit_code = exit_code;
+            if !definition.autorestart {
+                current.state = if exit_code == Some(0) { ProcessState::Exited } else { ProcessState::Crashed };
+                return;
+            }
+            current.state = ProcessState::Backoff;
+            current.restarts += 1;
+            drop(current);
+
+            if started.elapsed() > Self::HEALTHY_AFTER {
+                backoff = Duration::from_secs(1);
+            }
+            let resume = Instant::now() + backoff;
+            while Instant::now() < resume && !stop.load(Ordering::SeqCst) {
+                thread::sleep(Duration::from_millis(200));
+            }
+            backoff = (backoff * 2).min(Self::MAX_BACKOFF);
+        }
+        let mut status = status.lock().unwrap();
+        status.state = ProcessState::Stopped;
+        status.pid = None;
+    }
+
+    fn spawn(definition: &ProcessDefinition, cwd: &str, log_file: &str) -> std::io::Result<Child> {
+        let log = OpenOptions::new().create(true).append(true).open(log_file)?;
+        Self::log(log_file, &format!("starting: {}", definition.command));
+        Command::new("sh")
+            .arg("-c")
+            .arg(&definition.command)
+            .current_dir(cwd)
+            .envs(&definition.env)
+            .stdin(Stdio::null())
+            .stdout(log.try_clone()?)
+            .stderr(log)
+            // Own process group, so `npm run dev` and its children stop together
+            .process_group(0)
+            .spawn()
+    }
+
+    // Exit code of the child, or None once it was stopped on request
+    fn wait(child: &mut Child, stop: &AtomicBool) -> Option<Option<i32>> {
+        loop {
+            if stop.load(Ordering::SeqCst) {
+                Self::terminate(child);
+                return None;
+            }
+            match child.try_wait() {
+                Ok(Some(status)) => return Some(status.code()),
+                Ok(None) => thread::sleep(Duration::from_millis(200)),
+                Err(_) => return Some(None),
+            }
+        }
+    }
+
+    fn terminate(child: &mut Child) {
+        let group = format!("-{}", child.id());
+        let _ = Command::new("kill").args(["-TERM", "--", &group]).status();
+        let deadline = Instant::now() + Self::STOP_TIMEOUT;
+        while Instant::now() < deadline {
+            if let Ok(Some(_)) = child.try_wait() {
+                return;
+            }
+            thread::sleep(Duration::from_millis(100));
+        }
+        let _ = Command::new("kill").args(["-KILL", "--", &group]).status();
+        let _ = child.wait();
+    }
+
+    fn log(log_file: &str, message: &str) {
+        let line = format!("[{}] valetui: {}\n", Local::now().format("%Y-%m-%d %H:%M:%S"), message);
+        if let Ok(mut file) = OpenOptions::new().create(true).append(true).open(log_file) {
+            let _ = file.write_all(line.as_bytes());
+        }
+    }
+}
@@ -0,0 +1,48 @@
+use regex::Regex;
+
+use crate::tunnels::{TunnelProvider, TunnelTarget};
+
+// Cloudflare quick tunnel, no account needed: https://<random>.trycloudflare.com
+pub struct Cloudflared {
+    binary: String,
+}
+
+impl Cloudflared {
+    pub fn new(binary: &str) -> Self {
+        Cloudflared { binary: binary.to_string() }
+    }
+}
+
+impl TunnelProvider for Cloudflared {
+    fn name(&self) -> &'static str {
+        "cloudflared"
+    }
+
+    fn label(&self) -> &'static str {
+        "Cloudflare Tunnel"
+    }
+
+    fn binary(&self) -> &str {
+        &self.binary
+    }
+
+    fn args(&self, target: &TunnelTarget) -> Vec<String> {
+        let mut args = vec![
+            "tunnel".to_string(),
+            "--no-autoupdate".to_string(),
+            "--url".to_string(),
+            target.origin(),
+            "--http-host-header".to_string(),
+            target.url.clone(),
+        ];
+        if target.secured {
+            args.extend(["--origin-server-name".to_string(), target.url.clone(), "--no-tls-verify".to_string()]);
+        }
+        args
+    }
+
+    fn parse_url(&self, line: &str) -> Option<String> {
+        let url = Regex::new(r"https://[a-z0-9\-]+\.trycloudflare\.com").unwrap();
+        url.find(line).map(|m| m.as_str().to_string())
+    }
+}
@@ -0,0 +1,39 @@
+use regex::Regex;
+
+use crate::tunnels::{TunnelProvider, TunnelTarget};
+
+// Expose by Beyond Code, it connects to the site by name so the Host header is already right
+pub struct Expose {
+    binary: String,
+}
+
+impl Expose {
+    pub fn new(binary: &str) -> Self {
+        Expose { binary: binary.to_string() }
+    }
+}
+
+impl TunnelProvider for Expose {
+    fn name(&self) -> &'static str {
+        "expose"
+    }
+
+    fn label(&self) -> &'static str {
+        "Expose"
+    }
+
+    fn binary(&self) -> &str {
+        &self.binary
+    }
+
+    fn args(&self, target: &TunnelTarget) -> Vec<String> {
+        let scheme = if target.secured { "https" } else { "http" };
+        vec!["share".to_string(), format!("{}://{}:{}", scheme, target.url, target.port)]
+    }
+
+    // Public HTTPS:       https://abcd.sharedwithexpose.com
+    fn parse_url(&self, line: &str) -> Option<String> {
+        let url = Regex::new(r"Public HTTPS:\s+(https://\S+)").unwrap();
+        url.captures(line).map(|caps| caps[1].to_string())
+    }
+}
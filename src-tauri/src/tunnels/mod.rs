@@ -0,0 +1,452 @@
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read};
+use std::net::TcpListener;
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc;
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::configuration::Configuration;
+use crate::lan_share::LanShare;
+use crate::manager::file_system::ValetFilesystem;
+use crate::manager::interface::Filesystem;
+use crate::paths::{Paths, PathTrait};
+
+pub mod cloudflared;
+pub mod expose;
+pub mod ngrok;
+pub mod ssh;
+
+// Where a tunnel forwards to: nginx itself, or a local listener that sets the Host header
+#[derive(Serialize, Clone, Debug)]
+pub struct TunnelTarget {
+    pub site: String,
+    // The site's own hostname, e.g. blog.test
+    pub url: String,
+    pub port: u16,
+    pub secured: bool,
+}
+
+impl TunnelTarget {
+    pub fn origin(&self) -> String {
+        let scheme = if self.secured { "https" } else { "http" };
+        format!("{}://127.0.0.1:{}", scheme, self.port)
+    }
+}
+
+// A program that publishes a local port on a public url
+pub trait TunnelProvider: Send + Sync {
+    fn name(&self) -> &'static str;
+    fn label(&self) -> &'static str;
+    fn binary(&self) -> &str;
+    fn args(&self, target: &TunnelTarget) -> Vec<String>;
+    // The public url, once a line of the program's output announces it
+    fn parse_url(&self, line: &str) -> Option<String>;
+
+    // Drivers that cannot send the site's Host header themselves get a local listener that does
+    fn rewrites_host(&self) -> bool {
+        true
+    }
+
+    fn is_available(&self) -> bool {
+        Command::new("sh")
+            .arg("-c")
+            .arg(format!("command -v {} > /dev/null 2>&1", self.binary()))
+            .status()
+            .is_ok_and(|status| status.success())
+    }
+
+    fn info(&self) -> TunnelProviderInfo {
+        TunnelProviderInfo {
+            name: self.name().to_string(),
+            label: self.label().to_string(),
+            available: self.is_available(),
+        }
+    }
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct TunnelProviderInfo {
+    pub name: String,
+    pub label: String,
+    pub available: bool,
+}
+
+// Binaries can point at a stand-in, which is also how the drivers are tested
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct TunnelSettings {
+    pub cloudflared: String,
+    pub ngrok: String,
+    pub expose: String,
+    pub ssh: ssh::SshSettings,
+}
+
+impl Default for TunnelSettings {
+    fn default() -> Self {
+        Self {
+            cloudflared: "cloudflared".to_string(),
+            ngrok: "ngrok".to_string(),
+            expose: "expose".to_string(),
+            ssh: ssh::SshSettings::default(),
+        }
+    }
+}
+
+impl TunnelSettings {
+    pub fn load(config: &Configuration) -> Self {
+        config.get("tunnels")
+            .and_then(|settings| serde_json::from_value(settings).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, config: &Configuration) {
+        config.set("tunnels", json!(self));
+    }
+
+    pub fn providers(&self) -> Vec<Box<dyn TunnelProvider>> {
+        vec![
+            Box::new(cloudflared::Cloudflared::new(&self.cloudflared)),
+            Box::new(ngrok::Ngrok::new(&self.ngrok)),
+            Box::new(expose::Expose::new(&self.expose)),
+            Box::new(ssh::SshTunnel::new(self.ssh.clone())),
+        ]
+    }
+
+    pub fn provider(&self, name: &str) -> Result<Box<dyn TunnelProvider>, String> {
+        self.providers().into_iter()
+            .find(|provider| provider.name() == name)
+            .ok_or(format!("Unknown tunnel provider [{}].", name))
+    }
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct TunnelInfo {
+    pub site: String,
+    pub url: String,
+    pub provider: String,
+    pub public_url: String,
+    pub started_at: String,
+}
+
+struct ActiveTunnel {
+    info: TunnelInfo,
+    child: Child,
+    // Server block of the Host rewriting listener, when the driver needed one
+    listener: Option<String>,
+}
+
+// Public tunnels of the sites, one per site
+#[derive(Default)]
+pub struct Tunnels {
+    tunnels: Mutex<HashMap<String, ActiveTunnel>>,
+}
+
+impl Tunnels {
+    const LISTENER_PORTS: std::ops::RangeInclusive<u16> = 8200..=8299;
+    const URL_TIMEOUT: Duration = Duration::from_secs(30);
+
+    // Open a tunnel to the site, `reload` applies nginx changes for the Host rewriting listener
+    pub fn open<R>(&self, config: &Configuration, provider: &dyn TunnelProvider, site: &str, url: &str, secured: bool, reload: R) -> Result<TunnelInfo, String>
+    where
+        R: Fn() -> Result<(), String>,
+    {
+        self.close(site);
+        let mut listener = None;
+        let target = if provider.rewrites_host() {
+            let port = if secured { LanShare::port(config, "https_port", 443) } else { LanShare::port(config, "port", 80) };
+            TunnelTarget { site: site.to_string(), url: url.to_string(), port, secured }
+        } else {
+            let port = Self::LISTENER_PORTS.into_iter()
+                .find(|port| TcpListener::bind(("127.0.0.1", *port)).is_ok())
+                .ok_or("No free port left for the tunnel listener.".to_string())?;
+            let address = format!("127.0.0.1:{}", port);
+            let path = Paths::nginx_path(Some(&format!("{}.tunnel", url)));
+            let conf = LanShare::listener_config(config, url, secured, &address, &format!("http://{}", address))?;
+            ValetFilesystem.put(&path, &conf).map_err(|e| e.to_string())?;
+            listener = Some((path, address));
+            reload()?;
+            TunnelTarget { site: site.to_string(), url: url.to_string(), port, secured: false }
+        };
+
+        let remove_listener = |listener: &Option<(String, String)>| {
+            if let Some((path, _)) = listener {
+                let _ = ValetFilesystem.unlink(path);
+                let _ = reload();
+            }
+        };
+        let (mut child, public_url) = Self::spawn(provider, &target).inspect_err(|_| remove_listener(&listener))?;
+
+        // Absolute links now point at the public url instead of the listener
+        if let Some((path, address)) = &listener {
+            let rewritten = LanShare::listener_config(config, url, secured, address, &public_url)
+                .and_then(|conf| ValetFilesystem.put(path, &conf).map_err(|e| e.to_string()))
+                .and_then(|_| reload());
+            if let Err(error) = rewritten {
+                let _ = child.kill();
+                let _ = child.wait();
+                remove_listener(&listener);
+                return Err(error);
+            }
+        }
+
+        let info = TunnelInfo {
+            site: site.to_string(),
+            url: url.to_string(),
+            provider: provider.name().to_string(),
+            public_url,
+            started_at: Local::now().to_rfc3339(),
+        };
+        self.tunnels.lock().unwrap().insert(site.to_string(), ActiveTunnel {
+            info: info.clone(),
+            child,
+            listener: listener.map(|(path, _)| path),
+        });
+        Ok(info)
+    }
+
+    // Start the driver's program and wait for it to announce the public url
+    pub fn spawn(provider: &dyn TunnelProvider, target: &TunnelTarget) -> Result<(Child, String), String> {
+        let mut child = Command::new(provider.binary())
+            .args(provider.args(target))
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("Unable to start {}: {}", provider.label(), e))?;
+
+        let (sender, receiver) = mpsc::channel();
+        let stdout: Box<dyn Read + Send> = Box::new(child.stdout.take().unwrap());
+        let stderr: Box<dyn Read + Send> = Box::new(child.stderr.take().unwrap());
+        for stream in [stdout, stderr] {
+            let sender = sender.clone();
+            // Keeps draining after the url was found so the program never blocks on a full pipe
+            thread::spawn(move || {
+                for line in BufReader::new(stream).lines().map_while(Result::ok) {
+                    let _ = sender.send(line);
+                }
+            });
+        }
+        drop(sender);
+
+        let deadline = Instant::now() + Self::URL_TIMEOUT;
+        let mut output: Vec<String> = Vec::new();
+        while let Some(remaining) = deadline.checked_duration_since(Instant::now()) {
+            let Ok(line) = receiver.recv_timeout(remaining) else {
+                break;
+            };
+            if let Some(url) = provider.parse_url(&line) {
+                return Ok((child, url));
+            }
+            output.push(line);
+        }
+        let _ = child.kill();
+        let _ = child.wait();
+        let tail = output.len().saturating_sub(20);
+        Err(format!("{} did not report a public url:\n{}", provider.label(), output[tail..].join("\n")))
+    }
+
+    // False when the site had no tunnel
+    pub fn close(&self, site: &str) -> bool {
+        let Some(mut tunnel) = self.tunnels.lock().unwrap().remove(site) else {
+            return false;
+        };
+        let _ = tunnel.child.kill();
+        let _ = tunnel.child.wait();
+        if let Some(listener) = tunnel.listener {
+            let _ = ValetFilesystem.unlink(&listener);
+        }
+        true
+    }
+
+    // Used when valetui exits, true when nginx needs a restart to drop listeners
+    pub fn close_all(&self) -> bool {
+        let tunnels: Vec<(String, bool)> = self.tunnels.lock().unwrap().iter()
+            .map(|(site, tunnel)| (site.clone(), tunnel.listener.is_some()))
+            .collect();
+        let mut listeners = false;
+        for (site, listener) in tunnels {
+            listeners |= self.close(&site) && listener;
+        }
+        listeners
+    }
+
+    // Open tunnels, dropping the ones whose program exited on its own, true when nginx needs a restart to drop their listeners
+    pub fn tunnels(&self) -> (Vec<TunnelInfo>, bool) {
+        let mut tunnels = self.tunnels.lock().unwrap();
+        let exited: Vec<(String, bool)> = tunnels.iter_mut()
+            .filter_map(|(site, tunnel)| (!matches!(tunnel.child.try_wait(), Ok(None))).then(|| (site.clone(), tunnel.listener.is_some())))
+            .collect();
+        drop(tunnels);
+        let mut listeners = false;
+        for (site, listener) in exited {
+            listeners |= self.close(&site) && listener;
+        }
+        let mut tunnels: Vec<TunnelInfo> = self.tunnels.lock().unwrap().values().map(|tunnel| tunnel.info.clone()).collect();
+        tunnels.sort_by(|a, b| a.site.cmp(&b.site));
+        (tunnels, listeners)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+    use std::net::TcpStream;
+    use std::os::unix::fs::PermissionsExt;
+
+    use super::*;
+    use crate::nginx_config::NginxConfig;
+
+    fn target(port: u16, secured: bool) -> TunnelTarget {
+        TunnelTarget { site: "blog".to_string(), url: "blog.test".to_string(), port, secured }
+    }
+
+    // Executable script that stands in for the tunnel program
+    fn stand_in(name: &str, script: &str) -> String {
+        let path = std::env::temp_dir().join(format!("valetui-{}-{}", name, std::process::id()));
+        std::fs::write(&path, format!("#!/bin/sh\n{}\n", script)).unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+        path.to_string_lossy().to_string()
+    }
+
+    #[test]
+    fn drivers_send_the_site_host_header() {
+        let cloudflared = cloudflared::Cloudflared::new("cloudflared").args(&target(443, true));
+        assert!(cloudflared.windows(2).any(|w| w == ["--url", "https://127.0.0.1:443"]));
+        assert!(cloudflared.windows(2).any(|w| w == ["--http-host-header", "blog.test"]));
+        assert!(cloudflared.contains(&"--no-tls-verify".to_string()));
+
+        let ngrok = ngrok::Ngrok::new("ngrok").args(&target(80, false));
+        assert_eq!(ngrok[1], "http://127.0.0.1:80");
+        assert!(ngrok.contains(&"--host-header=blog.test".to_string()));
+
+        let expose = expose::Expose::new("expose").args(&target(80, false));
+        assert_eq!(expose, ["share", "http://blog.test:80"]);
+
+        let ssh = ssh::SshTunnel::new(ssh::SshSettings::default());
+        assert!(!ssh.rewrites_host());
+        let args = ssh.args(&target(8200, false));
+        assert!(args.windows(2).any(|w| w == ["-R", "80:127.0.0.1:8200"]));
+        assert_eq!(args.last().unwrap(), "nokey@localhost.run");
+    }
+
+    #[test]
+    fn drivers_parse_their_public_url() {
+        let cloudflared = cloudflared::Cloudflared::new("cloudflared");
+        assert_eq!(
+            cloudflared.parse_url("INF |  https://quiet-river-1234.trycloudflare.com  |").as_deref(),
+            Some("https://quiet-river-1234.trycloudflare.com"),
+        );
+        assert_eq!(cloudflared.parse_url("INF Requesting new quick Tunnel on trycloudflare.com..."), None);
+
+        let ngrok = ngrok::Ngrok::new("ngrok");
+        assert_eq!(
+            ngrok.parse_url(r#"lvl=info msg="started tunnel" obj=tunnels name=command_line addr=http://127.0.0.1:80 url=https://ab12.ngrok-free.app"#).as_deref(),
+            Some("https://ab12.ngrok-free.app"),
+        );
+
+        let expose = expose::Expose::new("expose");
+        assert_eq!(
+            expose.parse_url("Public HTTPS:       https://blog.sharedwithexpose.com").as_deref(),
+            Some("https://blog.sharedwithexpose.com"),
+        );
+
+        let ssh = ssh::SshTunnel::new(ssh::SshSettings::default());
+        assert_eq!(
+            ssh.parse_url("a1b2c3.lhr.life tunneled with tls termination, https://a1b2c3.lhr.life").as_deref(),
+            Some("https://a1b2c3.lhr.life"),
+        );
+    }
+
+    #[test]
+    fn spawn_waits_for_the_url_of_a_stand_in_sshd() {
+        let binary = stand_in("ssh", r#"echo "Welcome to the tunnel" >&2
+echo "forwarding $*" >&2
+echo "blog.lhr.life tunneled with tls termination, https://blog.lhr.life"
+sleep 30"#);
+        let ssh = ssh::SshTunnel::new(ssh::SshSettings { binary: binary.clone(), ..Default::default() });
+        let (mut child, url) = Tunnels::spawn(&ssh, &target(8200, false)).unwrap();
+        assert_eq!(url, "https://blog.lhr.life");
+        assert!(matches!(child.try_wait(), Ok(None)));
+        child.kill().unwrap();
+        child.wait().unwrap();
+        std::fs::remove_file(binary).unwrap();
+    }
+
+    #[test]
+    fn ssh_forwards_through_the_host_rewriting_listener() {
+        // The site as nginx serves it, answering with the Host header it was asked for
+        let site = TcpListener::bind("127.0.0.1:0").unwrap();
+        let upstream = format!("http://{}", site.local_addr().unwrap());
+        thread::spawn(move || {
+            let (mut stream, _) = site.accept().unwrap();
+            let host = read_head(&mut stream).into_iter()
+                .find_map(|line| line.strip_prefix("Host: ").map(|host| host.to_string()))
+                .unwrap_or_default();
+            stream.write_all(format!("HTTP/1.0 200 OK\r\nContent-Length: {}\r\n\r\n{}", host.len(), host).as_bytes()).unwrap();
+        });
+
+        // nginx is not around, a thread applies the Host header of the rendered listener instead
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let conf = LanShare::listener("blog.test", false, &format!("127.0.0.1:{}", port), &upstream, "http://127.0.0.1").unwrap();
+        let config = NginxConfig::parse(&conf).unwrap();
+        let host = config.find("proxy_set_header").into_iter()
+            .find(|directive| directive.args[0].value == "Host")
+            .map(|directive| directive.args[1].value.clone())
+            .unwrap();
+        let site_address = upstream.trim_start_matches("http://").to_string();
+        thread::spawn(move || {
+            let (mut client, _) = listener.accept().unwrap();
+            let head: Vec<String> = read_head(&mut client).into_iter()
+                .map(|line| if line.starts_with("Host: ") { format!("Host: {}", host) } else { line })
+                .collect();
+            let mut site = TcpStream::connect(site_address).unwrap();
+            site.write_all(format!("{}\r\n\r\n", head.join("\r\n")).as_bytes()).unwrap();
+            let mut response = Vec::new();
+            site.read_to_end(&mut response).unwrap();
+            client.write_all(&response).unwrap();
+        });
+
+        // The stand-in opens the -R forward itself and only announces a url when the site saw its own hostname
+        let binary = stand_in("ssh-forward", r#"while [ "$1" != "-R" ]; do shift; done
+served=$(curl -s -H "Host: blog.lhr.life" "http://127.0.0.1:${2##*:}/")
+[ "$served" = "blog.test" ] || { echo "the site saw [$served]" >&2; exit 1; }
+echo "blog.lhr.life tunneled with tls termination, https://blog.lhr.life"
+sleep 30"#);
+        let ssh = ssh::SshTunnel::new(ssh::SshSettings { binary: binary.clone(), ..Default::default() });
+        let result = Tunnels::spawn(&ssh, &target(port, false));
+        std::fs::remove_file(binary).unwrap();
+        let (mut child, url) = result.unwrap();
+        assert_eq!(url, "https://blog.lhr.life");
+        child.kill().unwrap();
+        child.wait().unwrap();
+    }
+
+    // Request or response head, without the blank line ending it
+    fn read_head(stream: &mut TcpStream) -> Vec<String> {
+        let mut reader = BufReader::new(stream);
+        let mut head = Vec::new();
+        loop {
+            let mut line = String::new();
+            if reader.read_line(&mut line).unwrap() == 0 || line.trim_end().is_empty() {
+                return head;
+            }
+            head.push(line.trim_end().to_string());
+        }
+    }
+
+    #[test]
+    fn spawn_reports_the_output_when_no_url_appears() {
+        let binary = stand_in("ngrok", r#"echo "ERROR: authentication failed" >&2
+exit 1"#);
+        let error = Tunnels::spawn(&ngrok::Ngrok::new(&binary), &target(80, false)).unwrap_err();
+        assert!(error.contains("authentication failed"), "{}", error);
+        std::fs::remove_file(binary).unwrap();
+    }
+}
@@ -0,0 +1,44 @@
+use regex::Regex;
+
+use crate::tunnels::{TunnelProvider, TunnelTarget};
+
+// ngrok v3, the authtoken comes from ngrok's own configuration
+pub struct Ngrok {
+    binary: String,
+}
+
+impl Ngrok {
+    pub fn new(binary: &str) -> Self {
+        Ngrok { binary: binary.to_string() }
+    }
+}
+
+impl TunnelProvider for Ngrok {
+    fn name(&self) -> &'static str {
+        "ngrok"
+    }
+
+    fn label(&self) -> &'static str {
+        "ngrok"
+    }
+
+    fn binary(&self) -> &str {
+        &self.binary
+    }
+
+    fn args(&self, target: &TunnelTarget) -> Vec<String> {
+        vec![
+            "http".to_string(),
+            target.origin(),
+            format!("--host-header={}", target.url),
+            "--log=stdout".to_string(),
+            "--log-format=logfmt".to_string(),
+        ]
+    }
+
+    // t=... lvl=info msg="started tunnel" obj=tunnels name=command_line addr=http://127.0.0.1:80 url=https://abcd.ngrok-free.app
+    fn parse_url(&self, line: &str) -> Option<String> {
+        let url = Regex::new(r"\burl=(https://\S+)").unwrap();
+        url.captures(line).map(|caps| caps[1].to_string())
+    }
+}
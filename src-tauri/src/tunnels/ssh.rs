@@ -0,0 +1,82 @@
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::tunnels::{TunnelProvider, TunnelTarget};
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct SshSettings {
+    pub binary: String,
+    pub host: String,
+    pub user: String,
+    pub port: u16,
+    pub remote_port: u16,
+    pub identity_file: Option<String>,
+    // First capture group is the public url in the server's output
+    pub url_pattern: String,
+}
+
+impl Default for SshSettings {
+    fn default() -> Self {
+        Self {
+            binary: "ssh".to_string(),
+            host: "localhost.run".to_string(),
+            user: "nokey".to_string(),
+            port: 22,
+            remote_port: 80,
+            identity_file: None,
+            url_pattern: r"tunneled with tls termination, (https://\S+)".to_string(),
+        }
+    }
+}
+
+// Plain `ssh -R` reverse tunnel, localhost.run by default or any server printing the url
+pub struct SshTunnel {
+    settings: SshSettings,
+}
+
+impl SshTunnel {
+    pub fn new(settings: SshSettings) -> Self {
+        SshTunnel { settings }
+    }
+}
+
+impl TunnelProvider for SshTunnel {
+    fn name(&self) -> &'static str {
+        "ssh"
+    }
+
+    fn label(&self) -> &'static str {
+        "SSH reverse tunnel"
+    }
+
+    fn binary(&self) -> &str {
+        &self.settings.binary
+    }
+
+    fn args(&self, target: &TunnelTarget) -> Vec<String> {
+        let mut args = vec![
+            "-T".to_string(),
+            "-o".to_string(), "ExitOnForwardFailure=yes".to_string(),
+            "-o".to_string(), "ServerAliveInterval=30".to_string(),
+            "-o".to_string(), "StrictHostKeyChecking=accept-new".to_string(),
+            "-p".to_string(), self.settings.port.to_string(),
+            "-R".to_string(), format!("{}:127.0.0.1:{}", self.settings.remote_port, target.port),
+        ];
+        if let Some(identity_file) = &self.settings.identity_file {
+            args.extend(["-i".to_string(), identity_file.clone()]);
+        }
+        args.push(format!("{}@{}", self.settings.user, self.settings.host));
+        args
+    }
+
+    fn parse_url(&self, line: &str) -> Option<String> {
+        let url = Regex::new(&self.settings.url_pattern).ok()?;
+        url.captures(line).and_then(|caps| caps.get(1)).map(|m| m.as_str().to_string())
+    }
+
+    // ssh only forwards bytes, the site's Host header is set by a local listener
+    fn rewrites_host(&self) -> bool {
+        false
+    }
+}